@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use walkdir::WalkDir;
+
+use crate::export::{self, ExportFormat};
+use crate::image_process::Filter;
+use crate::metadata;
+
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "tif", "tiff", "bmp"]; // Extensões procuradas nas pastas
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchError { // Falha em um arquivo do lote (os outros continuam)
+    pub path: PathBuf,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchReport { // Resultado do processamento de uma pasta
+    pub processed: Vec<PathBuf>, // Arquivos gravados na pasta de saída
+    pub failed: Vec<BatchError>,
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SUPPORTED_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+pub fn discover(input_root: &Path, output_root: Option<&Path>) -> (Vec<PathBuf>, Vec<BatchError>) { // Procura as imagens da pasta recursivamente (ignorando a pasta de saída, se houver)
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+
+    let output_root = output_root.and_then(|root| root.canonicalize().ok()); // Caminho real da saída ("./saida", links simbólicos...); ainda não existe no primeiro lote
+    let walker = WalkDir::new(input_root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |entry| {
+            !entry.file_type().is_dir() || output_root.is_none() || entry.path().canonicalize().ok() != output_root
+        });
+    for entry in walker {
+        match entry {
+            Ok(entry) if entry.file_type().is_file() && is_supported(entry.path()) => files.push(entry.into_path()),
+            Ok(_) => {}
+            Err(e) => errors.push(BatchError {
+                path: e.path().map_or_else(|| input_root.to_path_buf(), Path::to_path_buf),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    (files, errors)
+}
+
+pub fn output_path_for(file: &Path, input_root: &Path, output_root: &Path, format: ExportFormat) -> PathBuf { // Mesmo caminho relativo, dentro da pasta de saída
+    let relative = file.strip_prefix(input_root).unwrap_or(file);
+    output_root.join(relative).with_extension(format.extension())
+}
+
+fn output_format(file: &Path, format: Option<ExportFormat>) -> ExportFormat { // Sem formato escolhido, mantém o formato do arquivo original
+    format
+        .or_else(|| file.extension().and_then(|extension| extension.to_str()).and_then(ExportFormat::from_extension))
+        .unwrap_or(ExportFormat::Png)
+}
+
+fn claim_outputs(files: Vec<PathBuf>, input_root: &Path, output_root: &Path, format: Option<ExportFormat>) -> (Vec<PathBuf>, Vec<BatchError>) { // Arquivos que iriam para a mesma saída (ex.: a.jpg e a.png) não são sobrescritos: só o primeiro é processado
+    let mut claimed: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut collisions = Vec::new();
+    let mut unique = Vec::new();
+    for file in files {
+        let output = output_path_for(&file, input_root, output_root, output_format(&file, format));
+        match claimed.get(&output) {
+            Some(first) => collisions.push(BatchError {
+                message: format!("a saída {} já é usada por {}", output.display(), first.display()),
+                path: file,
+            }),
+            None => {
+                claimed.insert(output, file.clone());
+                unique.push(file);
+            }
+        }
+    }
+    (unique, collisions)
+}
+
+pub fn process_file(file: &Path, input_root: &Path, output_root: &Path, filters: &[Filter], format: Option<ExportFormat>) -> anyhow::Result<PathBuf> { // Aplica a cadeia de filtros em um arquivo
+    let format = output_format(file, format);
+    let output = output_path_for(file, input_root, output_root, format);
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("criação da pasta {}", parent.display()))?;
+    }
+
+    let (mut result, metadata) = metadata::load_oriented(&file.display().to_string())?;
+    for filter in filters {
+        result = filter.apply(&result)?;
+    }
+    Ok(export::export(&result, &output, format, metadata.as_ref())?)
+}
+
+pub fn run(input_root: &Path, output_root: &Path, filters: &[Filter], format: Option<ExportFormat>, mut progress: impl FnMut(usize, usize, &Path)) -> BatchReport { // Processa a pasta inteira, avisando o progresso a cada arquivo
+    let (files, mut failed) = discover(input_root, Some(output_root));
+    let (files, collisions) = claim_outputs(files, input_root, output_root, format);
+    failed.extend(collisions);
+    let mut report = BatchReport { processed: Vec::new(), failed };
+
+    for (index, file) in files.iter().enumerate() {
+        progress(index, files.len(), file);
+        match process_file(file, input_root, output_root, filters, format) {
+            Ok(output) => report.processed.push(output),
+            Err(e) => report.failed.push(BatchError { path: file.clone(), message: format!("{:#}", e) }),
+        }
+    }
+    progress(files.len(), files.len(), input_root);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf { // Pasta vazia e exclusiva do teste
+        let dir = std::env::temp_dir().join(format!("imagevisualizer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn outputs_with_the_same_name_are_reported() {
+        let input = Path::new("fotos");
+        let output = Path::new("saida");
+        let files = vec![input.join("a.jpg"), input.join("a.png"), input.join("b.png")];
+
+        let (unique, collisions) = claim_outputs(files.clone(), input, output, Some(ExportFormat::Png));
+        assert_eq!(unique, vec![input.join("a.jpg"), input.join("b.png")]);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].path, input.join("a.png"));
+
+        let (unique, collisions) = claim_outputs(files.clone(), input, output, None); // Mantendo o formato, as extensões continuam diferentes
+        assert_eq!(unique, files);
+        assert!(collisions.is_empty());
+    }
+
+    #[test]
+    fn discover_skips_the_output_folder_given_by_another_path() {
+        let input = temp_dir("discover");
+        std::fs::create_dir_all(input.join("saida")).unwrap();
+        std::fs::write(input.join("foto.png"), b"").unwrap();
+        std::fs::write(input.join("saida").join("foto.png"), b"").unwrap();
+
+        let output = input.join("saida").join("..").join("saida"); // Mesmo lugar, escrito de outro jeito
+        let (files, errors) = discover(&input, Some(&output));
+        assert_eq!(files, vec![input.join("foto.png")]);
+        assert!(errors.is_empty());
+
+        std::fs::remove_dir_all(&input).unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use crate::batch;
+use crate::export::ExportFormat;
+use crate::image_process::Filter;
+use crate::lenses;
+use crate::metadata::MetadataMode;
+use crate::presets;
+use crate::session::EditSession;
+
+const USAGE: &str = "Uso:
+  ImageVisualizer                                   abre a interface gráfica
+  ImageVisualizer apply [opções] <entrada> -o <saída>
+  ImageVisualizer batch [opções] <pasta> -o <pasta de saída>
+  ImageVisualizer filters                           lista os filtros e parâmetros
+  ImageVisualizer presets                           lista os presets salvos
+  ImageVisualizer calibrate [opções] <pasta>        calibra uma lente com fotos de um tabuleiro de xadrez
+  ImageVisualizer lenses                            lista os perfis de lente salvos
+
+Opções do apply:
+  -f, --filter <filtro>   filtro a aplicar, na ordem (pode repetir). Ex.: blur:sigma=2
+  -p, --preset <preset>   aplica os filtros de um preset (nome salvo ou arquivo .toml)
+  -l, --lens <perfil>     corrige a distorção com um perfil de lente (nome salvo ou arquivo .toml)
+  -o, --output <arquivo>  arquivo de saída (o formato vem da extensão)
+  -q, --quality <0-100>   qualidade para JPEG e WebP
+      --format <ext>      formato de saída (png, jpg, webp, tiff, bmp); troca a extensão do arquivo de saída
+
+Opções do batch (além de --filter, --preset e --quality):
+  -o, --output <pasta>    pasta de saída (a estrutura de subpastas é mantida)
+      --format <ext>      formato de saída (png, jpg, webp, tiff, bmp); padrão: o do arquivo original
+
+Opções do calibrate:
+  -n, --name <nome>       nome do perfil salvo
+  -b, --board <CxL>       cantos internos do tabuleiro (colunas x linhas); padrão: 9x6";
+
+pub fn run(args: &[String]) -> anyhow::Result<()> { // Modo de linha de comando (sem abrir janela)
+    match args.first().map(String::as_str) {
+        Some("apply") => apply(&args[1..]),
+        Some("batch") => batch(&args[1..]),
+        Some("filters") => {
+            list_filters();
+            Ok(())
+        }
+        Some("presets") => {
+            list_presets();
+            Ok(())
+        }
+        Some("calibrate") => calibrate(&args[1..]),
+        Some("lenses") => {
+            list_lenses();
+            Ok(())
+        }
+        Some("-h" | "--help" | "help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => bail!("comando desconhecido: {}\n\n{}", command, USAGE),
+        None => bail!("{}", USAGE),
+    }
+}
+
+struct ApplyOptions { // Opções lidas da linha de comando do apply e do batch
+    filters: Vec<Filter>,
+    input: PathBuf,
+    output: PathBuf,
+    quality: Option<i32>,
+    format: Option<String>, // Extensão escolhida com --format
+}
+
+fn parse_apply(args: &[String]) -> anyhow::Result<ApplyOptions> {
+    let mut filters = Vec::new();
+    let mut input = None;
+    let mut output = None;
+    let mut quality = None;
+    let mut format = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--filter" => {
+                let spec = args.next().context("--filter precisa de um valor")?;
+                filters.push(spec.parse::<Filter>()?);
+            }
+            "-p" | "--preset" => {
+                let name = args.next().context("--preset precisa de um valor")?;
+                filters.extend(presets::find(name)?.filters);
+            }
+            "-l" | "--lens" => {
+                let name = args.next().context("--lens precisa de um valor")?;
+                filters.push(Filter::Undistort { lens: lenses::find(name)?.profile, alpha: 0.0 });
+            }
+            "-o" | "--output" => {
+                output = Some(PathBuf::from(args.next().context("--output precisa de um valor")?));
+            }
+            "-q" | "--quality" => {
+                let value = args.next().context("--quality precisa de um valor")?;
+                quality = Some(value.parse::<i32>().with_context(|| format!("qualidade inválida: {}", value))?);
+            }
+            "--format" => {
+                format = Some(args.next().context("--format precisa de um valor")?.clone());
+            }
+            option if option.starts_with('-') => bail!("opção desconhecida: {}\n\n{}", option, USAGE),
+            path => {
+                if input.replace(PathBuf::from(path)).is_some() {
+                    bail!("informe apenas uma entrada");
+                }
+            }
+        }
+    }
+
+    Ok(ApplyOptions {
+        filters,
+        input: input.with_context(|| format!("entrada não informada\n\n{}", USAGE))?,
+        output: output.with_context(|| format!("saída não informada (-o)\n\n{}", USAGE))?,
+        quality,
+        format,
+    })
+}
+
+fn parse_format(extension: &str, quality: Option<i32>) -> anyhow::Result<ExportFormat> { // Formato escolhido com --format
+    let format = ExportFormat::from_extension(extension).with_context(|| format!("formato de saída não suportado: {}", extension))?;
+    Ok(quality.map_or(format, |quality| format.with_quality(quality)))
+}
+
+fn output_format(options: &ApplyOptions) -> anyhow::Result<ExportFormat> { // --format tem prioridade; sem ele, o formato vem da extensão do arquivo de saída
+    if let Some(extension) = &options.format {
+        return parse_format(extension, options.quality);
+    }
+    let path = &options.output;
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+    let format = ExportFormat::from_extension(extension)
+        .with_context(|| format!("formato de saída não suportado: {}", path.display()))?;
+    Ok(options.quality.map_or(format, |quality| format.with_quality(quality)))
+}
+
+fn apply(args: &[String]) -> anyhow::Result<()> { // Aplica os filtros usando o mesmo motor da interface
+    let options = parse_apply(args)?;
+    let format = output_format(&options)?;
+
+    let mut session = EditSession::open(&options.input.display().to_string())?;
+    session.update(|stack| {
+        for filter in &options.filters {
+            stack.push(filter.clone());
+        }
+    })?;
+    let metadata_warning = session.metadata_warning(format, MetadataMode::Preserve);
+    for warning in [session.upscale_warning(), session.precision_warning(), metadata_warning].into_iter().flatten() {
+        eprintln!("Aviso: {}", warning);
+    }
+    let saved = session.export(&options.output, format, MetadataMode::Preserve)?;
+
+    println!("{} filtro(s) aplicado(s), imagem salva em {}", options.filters.len(), saved.display());
+    Ok(())
+}
+
+fn batch(args: &[String]) -> anyhow::Result<()> { // Processa uma pasta inteira com a mesma cadeia de filtros
+    let options = parse_apply(args)?;
+    if !options.input.is_dir() {
+        bail!("{} não é uma pasta", options.input.display());
+    }
+    let format = options.format.as_deref().map(|extension| parse_format(extension, options.quality)).transpose()?;
+
+    if let Some(format) = format.filter(|format| !format.supports_metadata()) {
+        eprintln!("Aviso: o formato {} não grava EXIF; as imagens serão salvas sem metadados", format.label());
+    }
+    let report = batch::run(&options.input, &options.output, &options.filters, format, |index, total, file| {
+        if index < total {
+            eprintln!("[{}/{}] {}", index + 1, total, file.display());
+        }
+    });
+
+    println!("{} imagem(ns) salva(s) em {}", report.processed.len(), options.output.display());
+    if !report.failed.is_empty() {
+        eprintln!("{} arquivo(s) com erro:", report.failed.len());
+        for failure in &report.failed {
+            eprintln!("  {}: {}", failure.path.display(), failure.message);
+        }
+        bail!("o lote terminou com erros");
+    }
+    Ok(())
+}
+
+fn list_filters() { // Mostra os filtros disponíveis e os parâmetros aceitos
+    for name in Filter::NAMES {
+        if let Ok(filter) = Filter::with_defaults(name) {
+            println!("{:<16} {}", name, filter.label());
+            for param in filter.params() {
+                match param.option_key() {
+                    Some(default) => {
+                        let options: Vec<&str> = param.options.iter().map(|(key, _)| *key).collect();
+                        println!("    {:<12} {} (padrão {}, opções: {})", param.key, param.label, default, options.join(", "));
+                    }
+                    None => println!("    {:<12} {} (padrão {}, de {} a {})", param.key, param.label, param.value, param.min, param.max),
+                }
+            }
+        }
+    }
+}
+
+fn list_presets() { // Mostra os presets salvos na pasta de configuração
+    let saved = presets::list();
+    if saved.is_empty() {
+        println!("Nenhum preset salvo");
+    }
+    for preset in saved {
+        let filters: Vec<String> = preset.filters.iter().map(Filter::to_string).collect();
+        println!("{:<20} {}", preset.name, filters.join(" → "));
+    }
+    if let Some(dir) = presets::presets_dir() {
+        println!("\nPasta dos presets: {}", dir.display());
+    }
+}
+
+fn calibrate(args: &[String]) -> anyhow::Result<()> { // Calcula e salva um perfil de lente a partir das fotos do tabuleiro
+    let mut name = None;
+    let mut board = lenses::DEFAULT_BOARD;
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-n" | "--name" => name = Some(args.next().context("--name precisa de um valor")?.clone()),
+            "-b" | "--board" => board = lenses::parse_board(args.next().context("--board precisa de um valor")?)?,
+            option if option.starts_with('-') => bail!("opção desconhecida: {}\n\n{}", option, USAGE),
+            path => {
+                if input.replace(PathBuf::from(path)).is_some() {
+                    bail!("informe apenas uma pasta");
+                }
+            }
+        }
+    }
+    let input = input.with_context(|| format!("pasta das fotos não informada\n\n{}", USAGE))?;
+    let name = name.with_context(|| format!("nome do perfil não informado (--name)\n\n{}", USAGE))?;
+    if !input.is_dir() {
+        bail!("{} não é uma pasta", input.display());
+    }
+
+    let calibration = lenses::calibrate_folder(&input, &name, board, |index, total, file| {
+        if index < total {
+            eprintln!("[{}/{}] {}", index + 1, total, file.display());
+        }
+    })?;
+    for failure in &calibration.rejected {
+        eprintln!("Ignorada: {}: {}", failure.path.display(), failure.message);
+    }
+    let saved = lenses::save(&calibration.lens)?;
+
+    println!(
+        "Perfil \"{}\" calculado com {} foto(s), erro médio de {:.3} px, salvo em {}",
+        calibration.lens.name,
+        calibration.lens.images,
+        calibration.lens.error,
+        saved.display()
+    );
+    Ok(())
+}
+
+fn list_lenses() { // Mostra os perfis de lente salvos
+    let saved = lenses::list();
+    if saved.is_empty() {
+        println!("Nenhum perfil de lente salvo");
+    }
+    for lens in saved {
+        println!(
+            "{:<20} k1={:.4} k2={:.4} ({} fotos, erro {:.3} px)",
+            lens.name, lens.profile.k1, lens.profile.k2, lens.images, lens.error
+        );
+    }
+    if let Some(dir) = lenses::lenses_dir() {
+        println!("\nPasta dos perfis: {}", dir.display());
+    }
+}
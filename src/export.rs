@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use opencv::{core, imgcodecs, prelude::*};
+
+use crate::image_process::{self, Result};
+use crate::metadata::{self, Metadata};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat { // Formatos de saída suportados na exportação
+    Png,
+    Jpeg { quality: i32 }, // Qualidade de 0 a 100
+    WebP { quality: i32 }, // Qualidade de 1 a 100 (acima de 100 é sem perdas)
+    Tiff,
+    Bmp,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 5] = [ // Formatos na ordem mostrada na interface
+        ExportFormat::Png,
+        ExportFormat::Jpeg { quality: 95 },
+        ExportFormat::WebP { quality: 90 },
+        ExportFormat::Tiff,
+        ExportFormat::Bmp,
+    ];
+
+    pub fn from_extension(extension: &str) -> Option<Self> { // Descobre o formato pela extensão do arquivo (com a qualidade padrão)
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(ExportFormat::Png),
+            "jpg" | "jpeg" => Some(ExportFormat::Jpeg { quality: 95 }),
+            "webp" => Some(ExportFormat::WebP { quality: 90 }),
+            "tif" | "tiff" => Some(ExportFormat::Tiff),
+            "bmp" => Some(ExportFormat::Bmp),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg { .. } => "jpg",
+            ExportFormat::WebP { .. } => "webp",
+            ExportFormat::Tiff => "tiff",
+            ExportFormat::Bmp => "bmp",
+        }
+    }
+
+    pub fn label(&self) -> &'static str { // Nome mostrado na interface
+        match self {
+            ExportFormat::Png => "PNG",
+            ExportFormat::Jpeg { .. } => "JPEG",
+            ExportFormat::WebP { .. } => "WebP",
+            ExportFormat::Tiff => "TIFF",
+            ExportFormat::Bmp => "BMP",
+        }
+    }
+
+    pub fn quality(&self) -> Option<i32> { // Só JPEG e WebP têm qualidade ajustável
+        match self {
+            ExportFormat::Jpeg { quality } | ExportFormat::WebP { quality } => Some(*quality),
+            _ => None,
+        }
+    }
+
+    pub fn with_quality(self, quality: i32) -> Self {
+        match self {
+            ExportFormat::Jpeg { .. } => ExportFormat::Jpeg { quality: quality.clamp(0, 100) },
+            ExportFormat::WebP { .. } => ExportFormat::WebP { quality: quality.clamp(1, 100) },
+            other => other,
+        }
+    }
+
+    fn supports_alpha(&self) -> bool { // Formatos que gravam a transparência
+        matches!(self, ExportFormat::Png | ExportFormat::WebP { .. } | ExportFormat::Tiff)
+    }
+
+    fn supports_16bit(&self) -> bool { // Formatos que gravam 16 bits por canal
+        matches!(self, ExportFormat::Png | ExportFormat::Tiff)
+    }
+
+    pub fn supports_metadata(&self) -> bool { // Formatos em que o EXIF é inserido depois do imwrite
+        matches!(self, ExportFormat::Png | ExportFormat::Jpeg { .. })
+    }
+
+    fn params(&self) -> core::Vector<i32> { // Parâmetros IMWRITE_* passados ao imwrite
+        match self {
+            ExportFormat::Png => core::Vector::from_slice(&[imgcodecs::IMWRITE_PNG_COMPRESSION, 6]),
+            ExportFormat::Jpeg { quality } => core::Vector::from_slice(&[imgcodecs::IMWRITE_JPEG_QUALITY, *quality]),
+            ExportFormat::WebP { quality } => core::Vector::from_slice(&[imgcodecs::IMWRITE_WEBP_QUALITY, *quality]),
+            ExportFormat::Tiff => core::Vector::from_slice(&[imgcodecs::IMWRITE_TIFF_COMPRESSION, 5]), // Compressão LZW
+            ExportFormat::Bmp => core::Vector::new(),
+        }
+    }
+}
+
+pub fn output_path(path: &Path, format: ExportFormat) -> PathBuf { // Garante que a extensão do arquivo corresponde ao formato escolhido
+    let matches = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(ExportFormat::from_extension)
+        .is_some_and(|found| found.extension() == format.extension());
+    if matches {
+        path.to_path_buf()
+    } else {
+        path.with_extension(format.extension())
+    }
+}
+
+pub fn export(img: &Mat, path: &Path, format: ExportFormat, metadata: Option<&Metadata>) -> Result<PathBuf> { // Codifica a imagem no formato escolhido e grava no disco
+    let path = output_path(path, format);
+    let mut img = img.clone();
+    if img.depth() != core::CV_8U && !format.supports_16bit() { // O imwrite cortaria os valores acima de 255 em vez de reescalar
+        img = image_process::to_8bit(&img)?;
+    }
+    if img.channels() == 4 && !format.supports_alpha() {
+        img = image_process::flatten_alpha(&image_process::to_8bit(&img)?)?; // Áreas transparentes ficam brancas
+    }
+    image_process::save_image(&path.display().to_string(), &img, &format.params())?;
+    if let Some(metadata) = metadata.filter(|_| format.supports_metadata()) { // O EXIF é inserido depois, porque o imwrite não grava metadados (ver EditSession::metadata_warning)
+        metadata::embed(&path, format, metadata)?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,269 @@
+use opencv::prelude::*;
+
+use crate::image_process::{Filter, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditOp { // Uma operação da pilha de edição
+    pub filter: Filter,
+    pub enabled: bool, // Operações desativadas continuam na lista, mas não são aplicadas
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EditStack { // Lista ordenada de operações, com desfazer/refazer
+    ops: Vec<EditOp>,
+    undo_stack: Vec<Vec<EditOp>>, // Estados anteriores da lista
+    redo_stack: Vec<Vec<EditOp>>, // Estados desfeitos que ainda podem ser refeitos
+    last_param_edit: Option<usize>, // Operação cujos parâmetros estão sendo ajustados (agrupa os movimentos do slider)
+}
+
+impl EditStack {
+    pub fn ops(&self) -> &[EditOp] {
+        &self.ops
+    }
+
+    pub fn enabled_filters(&self) -> Vec<Filter> { // Cadeia de filtros ativos (usada no processamento em lote)
+        self.ops.iter().filter(|op| op.enabled).map(|op| op.filter.clone()).collect()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    fn record(&mut self) { // Guarda o estado atual antes de uma alteração
+        self.undo_stack.push(self.ops.clone());
+        self.redo_stack.clear();
+        self.last_param_edit = None;
+    }
+
+    pub fn push(&mut self, filter: Filter) { // Adiciona uma operação no fim da lista
+        self.record();
+        self.ops.push(EditOp { filter, enabled: true });
+    }
+
+    pub fn extend(&mut self, filters: &[Filter]) { // Adiciona vários filtros de uma vez (um único passo de desfazer)
+        if !filters.is_empty() {
+            self.record();
+            self.ops.extend(filters.iter().cloned().map(|filter| EditOp { filter, enabled: true }));
+        }
+    }
+
+    pub fn set_filter(&mut self, index: usize, filter: Filter) { // Troca os parâmetros de uma operação (um arraste de slider vira um único passo de desfazer)
+        if index < self.ops.len() && self.ops[index].filter != filter {
+            if self.last_param_edit != Some(index) {
+                self.record();
+            }
+            self.ops[index].filter = filter;
+            self.last_param_edit = Some(index);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) { // Remove uma operação específica
+        if index < self.ops.len() {
+            self.record();
+            self.ops.remove(index);
+        }
+    }
+
+    pub fn toggle(&mut self, index: usize) { // Ativa ou desativa uma operação sem removê-la
+        if index < self.ops.len() {
+            self.record();
+            self.ops[index].enabled = !self.ops[index].enabled;
+        }
+    }
+
+    pub fn move_op(&mut self, from: usize, to: usize) { // Muda a posição de uma operação na lista
+        if from != to && from < self.ops.len() && to < self.ops.len() {
+            self.record();
+            let op = self.ops.remove(from);
+            self.ops.insert(to, op);
+        }
+    }
+
+    pub fn clear(&mut self) { // Remove todas as operações (o reset também pode ser desfeito)
+        if !self.ops.is_empty() {
+            self.record();
+            self.ops.clear();
+        }
+    }
+
+    pub fn undo(&mut self) {
+        self.last_param_edit = None;
+        if let Some(previous) = self.undo_stack.pop() {
+            self.redo_stack.push(std::mem::replace(&mut self.ops, previous));
+        }
+    }
+
+    pub fn redo(&mut self) {
+        self.last_param_edit = None;
+        if let Some(next) = self.redo_stack.pop() {
+            self.undo_stack.push(std::mem::replace(&mut self.ops, next));
+        }
+    }
+
+    pub fn render(&self, original: &Mat) -> Result<Mat> { // Reaplica as operações ativas a partir da imagem original
+        let mut result = original.clone();
+        for op in self.ops.iter().filter(|op| op.enabled) {
+            result = op.filter.apply(&result)?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contrast(alpha: f64) -> Filter {
+        Filter::Contrast { alpha, beta: 0.0 }
+    }
+
+    fn filters(stack: &EditStack) -> Vec<Filter> {
+        stack.ops().iter().map(|op| op.filter.clone()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_through_the_states() {
+        let mut stack = EditStack::default();
+        assert!(!stack.can_undo() && !stack.can_redo());
+
+        stack.push(Filter::Grayscale);
+        stack.push(Filter::Invert);
+        stack.undo();
+        assert_eq!(filters(&stack), vec![Filter::Grayscale]);
+        assert!(stack.can_redo());
+
+        stack.undo();
+        assert!(stack.ops().is_empty());
+        assert!(!stack.can_undo());
+
+        stack.redo();
+        stack.redo();
+        assert_eq!(filters(&stack), vec![Filter::Grayscale, Filter::Invert]);
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn new_push_clears_redo() {
+        let mut stack = EditStack::default();
+        stack.push(Filter::Grayscale);
+        stack.undo();
+        assert!(stack.can_redo());
+
+        stack.push(Filter::Invert);
+        assert!(!stack.can_redo());
+        stack.redo(); // Nada a refazer: a lista não muda
+        assert_eq!(filters(&stack), vec![Filter::Invert]);
+    }
+
+    #[test]
+    fn consecutive_slider_edits_are_one_undo_step() {
+        let mut stack = EditStack::default();
+        stack.push(contrast(1.0));
+        stack.set_filter(0, contrast(1.1));
+        stack.set_filter(0, contrast(1.2));
+        stack.set_filter(0, contrast(1.3));
+        assert_eq!(filters(&stack), vec![contrast(1.3)]);
+
+        stack.undo(); // Volta direto para o valor de antes do arraste
+        assert_eq!(filters(&stack), vec![contrast(1.0)]);
+        stack.undo();
+        assert!(stack.ops().is_empty());
+    }
+
+    #[test]
+    fn slider_edits_on_another_op_start_a_new_step() {
+        let mut stack = EditStack::default();
+        stack.extend(&[contrast(1.0), contrast(2.0)]);
+        stack.set_filter(0, contrast(1.5));
+        stack.set_filter(1, contrast(2.5));
+        stack.set_filter(0, contrast(1.7));
+
+        stack.undo();
+        assert_eq!(filters(&stack), vec![contrast(1.5), contrast(2.5)]);
+        stack.undo();
+        assert_eq!(filters(&stack), vec![contrast(1.5), contrast(2.0)]);
+        stack.undo();
+        assert_eq!(filters(&stack), vec![contrast(1.0), contrast(2.0)]);
+    }
+
+    #[test]
+    fn undo_ends_the_slider_group() {
+        let mut stack = EditStack::default();
+        stack.push(contrast(1.0));
+        stack.set_filter(0, contrast(1.2));
+        stack.undo();
+        stack.set_filter(0, contrast(1.4)); // Depois do desfazer, um novo arraste é um novo passo
+        stack.undo();
+        assert_eq!(filters(&stack), vec![contrast(1.0)]);
+    }
+
+    #[test]
+    fn setting_the_same_filter_records_nothing() {
+        let mut stack = EditStack::default();
+        stack.push(contrast(1.0));
+        stack.undo();
+        stack.redo();
+        stack.set_filter(0, contrast(1.0));
+        assert!(stack.can_undo());
+        stack.undo();
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn extend_is_a_single_undo_step() {
+        let mut stack = EditStack::default();
+        stack.extend(&[Filter::Grayscale, Filter::Invert]);
+        assert_eq!(stack.ops().len(), 2);
+        stack.undo();
+        assert!(stack.ops().is_empty());
+        assert!(!stack.can_undo());
+
+        stack.extend(&[]); // Lista vazia não cria um passo
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn move_op_reorders_and_can_be_undone() {
+        let mut stack = EditStack::default();
+        stack.extend(&[Filter::Grayscale, Filter::Invert, Filter::Sharpen]);
+        stack.move_op(0, 2);
+        assert_eq!(filters(&stack), vec![Filter::Invert, Filter::Sharpen, Filter::Grayscale]);
+        stack.move_op(1, 5); // Fora da lista: ignorado
+        stack.move_op(1, 1);
+        stack.undo();
+        assert_eq!(filters(&stack), vec![Filter::Grayscale, Filter::Invert, Filter::Sharpen]);
+    }
+
+    #[test]
+    fn toggle_keeps_the_op_but_skips_it() {
+        let mut stack = EditStack::default();
+        stack.extend(&[Filter::Grayscale, Filter::Invert]);
+        stack.toggle(0);
+        assert_eq!(stack.ops().len(), 2);
+        assert!(!stack.ops()[0].enabled);
+        assert_eq!(stack.enabled_filters(), vec![Filter::Invert]);
+
+        stack.undo();
+        assert!(stack.ops()[0].enabled);
+        assert_eq!(stack.enabled_filters(), vec![Filter::Grayscale, Filter::Invert]);
+    }
+
+    #[test]
+    fn remove_and_clear_can_be_undone() {
+        let mut stack = EditStack::default();
+        stack.extend(&[Filter::Grayscale, Filter::Invert]);
+        stack.remove(0);
+        assert_eq!(filters(&stack), vec![Filter::Invert]);
+        stack.clear();
+        assert!(stack.ops().is_empty());
+
+        stack.undo();
+        assert_eq!(filters(&stack), vec![Filter::Invert]);
+        stack.undo();
+        assert_eq!(filters(&stack), vec![Filter::Grayscale, Filter::Invert]);
+    }
+}
@@ -1,3 +1,7 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
 use opencv::{ // Importação de bibliotecas
     core,
     imgcodecs,
@@ -6,99 +10,917 @@ use opencv::{ // Importação de bibliotecas
     core::AlgorithmHint,
 };
 
-pub fn add_filter(input_image_path: String, filter_type: String) -> String { // Função para adicionar um filtro à imagem
-    let img = match imgcodecs::imread(&input_image_path, imgcodecs::IMREAD_COLOR) { // Carrega a imagem
-        Ok(mat) if !mat.empty() => mat,
-        Ok(_) => { // Caso a imagem esteja vazia
-            eprintln!("Erro: Imagem carregada de {} está vazia.", input_image_path);
-            return input_image_path; 
-        }
-        Err(e) => { // Caso ocorra um erro ao carregar a imagem (Requisito da ponderada)
-            eprintln!("Erro ao carregar a imagem de {}: {}", input_image_path, e);
-            return input_image_path;
-        }
-    };
-
-    let mut result = Mat::default(); // Cria uma matriz para armazenar o resultado
-
-    match filter_type.as_str() {
-        "grayscale" => { // Escala de cinza
-            imgproc::cvt_color(&img, &mut result, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT).unwrap();
-        }
-        "invert" => { // Inverte as cores
-            core::bitwise_not(&img, &mut result, &Mat::default()).unwrap();
-        }
-        "contrast" => { // Aumenta o contraste
-            img.convert_to(&mut result, -1, 1.5, 0.0).unwrap();
-        }
-        "blur" => { // Desfoca a imagem
-            imgproc::gaussian_blur(&img, &mut result, core::Size::new(5, 5), 0.0, 0.0, core::BORDER_DEFAULT, AlgorithmHint::ALGO_HINT_DEFAULT).unwrap();
-        }
-        "sharpen" => { // Aumenta a nitidez 
-            let kernel = Mat::from_slice_2d(&[
-                &[0.0, -1.0, 0.0],
-                &[-1.0, 5.0, -1.0],
-                &[0.0, -1.0, 0.0],
-            ]).unwrap();
-            imgproc::filter_2d(&img, &mut result, -1, &kernel, core::Point::new(-1, -1), 0.0, core::BORDER_DEFAULT).unwrap();
-        }
-        "edges" => { // Detecta as bordas
-            let mut gray = Mat::default();
-            imgproc::cvt_color(&img, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT).unwrap();
-            imgproc::canny(&gray, &mut result, 100.0, 200.0, 3, false).unwrap();
-        }
-        "resize_half" => { // reduz a resolução da imagem pela metade
-            imgproc::resize(&img, &mut result, core::Size::default(), 0.5, 0.5, imgproc::INTER_LINEAR).unwrap();
-        }
-        "rotate_90_cw" => { // gira a imagem 90 graus no sentido horário
-            core::rotate(&img, &mut result, core::ROTATE_90_CLOCKWISE).unwrap();
-        }
-        "rotate_90_ccw" => { // gira a imagem 90 graus no sentido anti-horário
-            core::rotate(&img, &mut result, core::ROTATE_90_COUNTERCLOCKWISE).unwrap();
-        }
-        "reset" => { // reseta a imagem para a original
-            result = img.clone();
-        }
-        _ => { // opção inválida
-            result = img.clone();
-        }
-    }
-
-
-    // Cria o nome do arquivo de saída (ainda não há exportação de arquivos)
-    let timestamp = std::time::SystemTime::now() 
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_or_else(|_| 0, |d| d.as_millis());
-
-    let original_path_obj = std::path::Path::new(&input_image_path);
-    
-    let parent_dir = original_path_obj
-        .parent()
-        .and_then(std::path::Path::to_str)
-        .unwrap_or(".");
-
-    let file_stem = original_path_obj
-        .file_stem()
-        .and_then(std::ffi::OsStr::to_str)
-        .unwrap_or("image")
-        .to_string()
-        .split('_') 
-        .next()
-        .unwrap_or("image")
-        .to_string();
-    
-    let output_path = format!(
-        "{}/{}_{}_{}_processed.jpg",
-        parent_dir,
-        file_stem,
-        filter_type,
-        timestamp
-    );
-        
-    if let Err(e) = imgcodecs::imwrite(&output_path, &result, &core::Vector::new()) {
-        eprintln!("Erro ao salvar a imagem processada em {}: {}", output_path, e);
-        return input_image_path;
-    }
-
-    output_path
-}
\ No newline at end of file
+mod color;
+mod convolution;
+mod denoise;
+mod edges;
+mod geometry;
+mod lens;
+mod morphology;
+mod perspective;
+mod pixels;
+mod sharpen;
+mod threshold;
+mod tone;
+
+pub use color::{sample_color, ChannelSwap, ColorChannel, ColorSpace, WhiteBalance};
+pub use convolution::{is_kernel_key, Kernel, KernelBorder, KERNEL_EXAMPLES, KERNEL_KEYS, KERNEL_MAX};
+pub use denoise::NlMeansMode;
+pub use edges::{EdgeDetector, EdgeDirection, EdgeOperator, EdgeOverlay};
+pub use geometry::{straighten_angle, BorderFill, Interpolation, ResizeMode, ResizeTarget, RotateMode};
+pub use lens::{calibrate, find_chessboard, LensProfile};
+pub use morphology::{MorphOperation, MorphShape};
+pub use perspective::{detect_document, Corners, FULL_FRAME};
+pub use pixels::{flatten_alpha, to_8bit};
+pub use sharpen::SharpenMode;
+pub use threshold::{Threshold, ThresholdMethod, ThresholdMode};
+pub use tone::{histogram, Curve, Histogram, Levels, CURVE_CHANNELS, CURVE_POINTS};
+
+#[derive(Debug)]
+pub enum ImageError { // Erros que podem acontecer ao processar uma imagem
+    UnknownFilter(String), // Nome de filtro que não existe
+    InvalidParam(String), // Parâmetro inexistente ou com valor inválido
+    Read(String, Option<opencv::Error>), // Arquivo inexistente, ilegível ou vazio (com o erro do OpenCV, quando houver)
+    Write(String), // O OpenCV não conseguiu gravar o arquivo
+    Metadata(anyhow::Error), // Falha ao ler ou gravar o EXIF
+    OpenCv(anyhow::Error), // Falha em alguma chamada do OpenCV (com o contexto da operação)
+    Other(anyhow::Error), // Outras falhas durante o processamento
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::UnknownFilter(name) => write!(f, "Filtro desconhecido: {}", name),
+            ImageError::InvalidParam(message) => write!(f, "Parâmetro inválido: {}", message),
+            ImageError::Read(path, None) => write!(f, "Não foi possível carregar a imagem de {}", path),
+            ImageError::Read(path, Some(e)) => write!(f, "Não foi possível carregar a imagem de {}: {}", path, e),
+            ImageError::Write(path) => write!(f, "Não foi possível salvar a imagem em {}", path),
+            ImageError::Metadata(e) => write!(f, "Erro nos metadados EXIF: {:#}", e),
+            ImageError::OpenCv(e) => write!(f, "Erro do OpenCV: {:#}", e),
+            ImageError::Other(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { // Mantém a causa original acessível (ex.: `{:#}` do anyhow na CLI)
+        match self {
+            ImageError::Read(_, Some(e)) => Some(e),
+            ImageError::Metadata(e) | ImageError::OpenCv(e) | ImageError::Other(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ImageError { // Permite usar `.context(...)?` nas chamadas do OpenCV
+    fn from(e: anyhow::Error) -> Self {
+        if e.chain().any(|cause| cause.is::<opencv::Error>()) {
+            ImageError::OpenCv(e)
+        } else {
+            ImageError::Other(e)
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ImageError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Param { // Descrição de um parâmetro ajustável (usada pelos sliders da interface)
+    pub key: &'static str, // Nome curto do parâmetro
+    pub label: &'static str, // Nome mostrado na interface
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    pub options: &'static [(&'static str, &'static str)], // Opções nomeadas (chave, rótulo); o valor é o índice da opção
+}
+
+impl Param {
+    fn new(key: &'static str, label: &'static str, value: f64, min: f64, max: f64, step: f64) -> Self {
+        Param { key, label, value, min, max, step, options: &[] }
+    }
+
+    fn choice(key: &'static str, label: &'static str, index: usize, options: &'static [(&'static str, &'static str)]) -> Self { // Parâmetro de escolha (vira um <select> na interface)
+        Param { key, label, value: index as f64, min: 0.0, max: (options.len() - 1) as f64, step: 1.0, options }
+    }
+
+    pub fn option_key(&self) -> Option<&'static str> { // Chave da opção escolhida, nos parâmetros de escolha
+        self.options.get(self.value.round().max(0.0) as usize).map(|(key, _)| *key)
+    }
+}
+
+fn option_index(value: f64, options: &[(&str, &str)]) -> usize { // Índice válido a partir do valor do slider ou do texto
+    (value.round().max(0.0) as usize).min(options.len() - 1)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter { // Filtros disponíveis, com os seus parâmetros
+    Grayscale,
+    Invert,
+    Contrast { alpha: f64, beta: f64 },
+    Blur { kernel_size: i32, sigma: f64 },
+    Sharpen,
+    Edges { detector: EdgeDetector }, // Canny, Sobel, Scharr ou Laplaciano, opcionalmente sobre a original
+    Resize { target: ResizeTarget, interpolation: Interpolation }, // Escala, tamanho exato, lado maior/menor ou caixa
+    Rotate90Clockwise,
+    Rotate90CounterClockwise,
+    Rotate { angle: f64, mode: RotateMode, border: BorderFill }, // Rotação livre, em graus (positivo = anti-horário)
+    FlipHorizontal,
+    FlipVertical,
+    Crop { x: f64, y: f64, width: f64, height: f64 }, // Retângulo relativo ao tamanho da imagem (0 a 1)
+    Levels { master: Levels, red: Levels, green: Levels, blue: Levels }, // Níveis gerais e por canal
+    Curves { curves: [Curve; 4] }, // Curvas geral, vermelha, verde e azul
+    Equalize, // Equalização do histograma da luminância
+    Clahe { clip_limit: f64, tile_size: i32 }, // Equalização adaptativa por blocos
+    ExtractChannel { channel: ColorChannel }, // Mostra um canal sozinho, em cinza
+    SwapChannels { swap: ChannelSwap },
+    HueSaturation { hue: f64, saturation: f64, lightness: f64 }, // Matiz em graus; saturação e luminosidade em %
+    ChannelAdjust { space: ColorSpace, channel: usize, gain: f64, offset: f64 }, // Ganho e deslocamento de um canal em HSV/HLS/Lab/YCrCb
+    AutoWhiteBalance { method: WhiteBalance, percentile: f64 }, // Percentil usado como branco no método de ponto branco
+    Temperature { temperature: f64, tint: f64 }, // De -100 a 100
+    NeutralPoint { red: f64, green: f64, blue: f64 }, // Cor que deve virar cinza (escolhida com o conta-gotas)
+    Median { kernel_size: i32 },
+    Bilateral { diameter: i32, sigma_color: f64, sigma_space: f64 },
+    NlMeans { mode: NlMeansMode, strength: f64, color_strength: f64, template_size: i32, search_size: i32 },
+    UnsharpMask { mode: SharpenMode, radius: f64, amount: f64, threshold: f64, relative: bool }, // Raio relativo = por 1000 px do lado maior
+    Convolution { kernel: Kernel }, // Kernel NxM definido pelo usuário
+    Morphology { operation: MorphOperation, shape: MorphShape, size: i32, iterations: i32 },
+    Threshold { threshold: Threshold }, // Binarização fixa, automática (Otsu, triângulo) ou adaptativa
+    Perspective { corners: Corners }, // Quadrilátero (documento, quadro) endireitado num retângulo
+    Undistort { lens: LensProfile, alpha: f64 }, // Perfil de lente calibrado; alpha 0 = só pixels válidos, 1 = imagem inteira
+}
+
+impl FromStr for Filter {
+    type Err = ImageError;
+
+    fn from_str(spec: &str) -> Result<Self> { // Lê "nome" ou "nome:chave=valor,chave=valor" (ex.: "blur:sigma=2")
+        let (name, params) = spec.split_once(':').unwrap_or((spec, ""));
+        let mut filter = Filter::with_defaults(name.trim())?;
+        for pair in params.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| ImageError::InvalidParam(format!("esperado chave=valor em \"{}\"", pair)))?;
+            let option = filter // Parâmetros de escolha aceitam o nome da opção (ex.: "rotate:mode=expand")
+                .params()
+                .into_iter()
+                .find(|param| param.key == key.trim())
+                .and_then(|param| param.options.iter().position(|(option, _)| *option == value.trim()));
+            let value = match option {
+                Some(index) => index as f64,
+                None => value
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| ImageError::InvalidParam(format!("{} não é um número em \"{}\"", value.trim(), pair)))?,
+            };
+            filter.set_param(key.trim(), value)?;
+        }
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for Filter { // Escreve o filtro no mesmo formato aceito pelo `from_str`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        for (index, param) in self.params().iter().enumerate() {
+            let separator = if index == 0 { ':' } else { ',' };
+            match param.option_key() {
+                Some(option) => write!(f, "{}{}={}", separator, param.key, option)?,
+                None => write!(f, "{}{}={}", separator, param.key, param.value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Filter {
+    pub const NAMES: &'static [&'static str] = &[ // Nomes aceitos pelo `from_str`
+        "grayscale",
+        "invert",
+        "contrast",
+        "blur",
+        "sharpen",
+        "edges",
+        "resize",
+        "rotate_90_cw",
+        "rotate_90_ccw",
+        "rotate",
+        "flip_h",
+        "flip_v",
+        "crop",
+        "levels",
+        "curves",
+        "equalize",
+        "clahe",
+        "channel",
+        "swap_channels",
+        "hue_saturation",
+        "channel_adjust",
+        "white_balance",
+        "temperature",
+        "neutral_point",
+        "median",
+        "bilateral",
+        "nlmeans",
+        "unsharp",
+        "kernel",
+        "morphology",
+        "threshold",
+        "perspective",
+        "undistort",
+    ];
+
+    pub fn with_defaults(name: &str) -> Result<Self> { // Cria o filtro pelo nome, com os valores padrão
+        match name {
+            "grayscale" => Ok(Filter::Grayscale),
+            "invert" => Ok(Filter::Invert),
+            "contrast" => Ok(Filter::Contrast { alpha: 1.5, beta: 0.0 }),
+            "blur" => Ok(Filter::Blur { kernel_size: 5, sigma: 0.0 }),
+            "sharpen" => Ok(Filter::Sharpen),
+            "edges" => Ok(Filter::Edges { detector: EdgeDetector::default() }),
+            "resize" | "resize_half" => Ok(Filter::Resize {
+                target: ResizeTarget { mode: ResizeMode::Scale, factor: 0.5, width: 1920, height: 1080, keep_aspect: true },
+                interpolation: Interpolation::Linear,
+            }),
+            "rotate_90_cw" => Ok(Filter::Rotate90Clockwise),
+            "rotate_90_ccw" => Ok(Filter::Rotate90CounterClockwise),
+            "rotate" => Ok(Filter::Rotate { angle: 0.0, mode: RotateMode::Expand, border: BorderFill::Black }),
+            "flip_h" => Ok(Filter::FlipHorizontal),
+            "flip_v" => Ok(Filter::FlipVertical),
+            "crop" => Ok(Filter::Crop { x: 0.0, y: 0.0, width: 1.0, height: 1.0 }),
+            "perspective" => Ok(Filter::Perspective { corners: FULL_FRAME }),
+            "undistort" => Ok(Filter::Undistort { lens: LensProfile::default(), alpha: 0.0 }),
+            "levels" => Ok(Filter::Levels { master: Levels::default(), red: Levels::default(), green: Levels::default(), blue: Levels::default() }),
+            "curves" => Ok(Filter::Curves { curves: [Curve::default(); 4] }),
+            "equalize" => Ok(Filter::Equalize),
+            "clahe" => Ok(Filter::Clahe { clip_limit: 2.0, tile_size: 8 }),
+            "channel" => Ok(Filter::ExtractChannel { channel: ColorChannel::Red }),
+            "swap_channels" => Ok(Filter::SwapChannels { swap: ChannelSwap::RedBlue }),
+            "hue_saturation" => Ok(Filter::HueSaturation { hue: 0.0, saturation: 0.0, lightness: 0.0 }),
+            "channel_adjust" => Ok(Filter::ChannelAdjust { space: ColorSpace::Hsv, channel: 1, gain: 1.0, offset: 0.0 }),
+            "white_balance" => Ok(Filter::AutoWhiteBalance { method: WhiteBalance::GrayWorld, percentile: 99.0 }),
+            "temperature" => Ok(Filter::Temperature { temperature: 0.0, tint: 0.0 }),
+            "neutral_point" => Ok(Filter::NeutralPoint { red: 128.0, green: 128.0, blue: 128.0 }),
+            "median" => Ok(Filter::Median { kernel_size: 5 }),
+            "bilateral" => Ok(Filter::Bilateral { diameter: 9, sigma_color: 75.0, sigma_space: 75.0 }),
+            "nlmeans" => Ok(Filter::NlMeans { mode: NlMeansMode::Colored, strength: 10.0, color_strength: 10.0, template_size: 7, search_size: 21 }),
+            "kernel" => Ok(Filter::Convolution { kernel: Kernel::default() }),
+            "threshold" => Ok(Filter::Threshold { threshold: Threshold::default() }),
+            "morphology" => Ok(Filter::Morphology { operation: MorphOperation::Open, shape: MorphShape::Rect, size: 3, iterations: 1 }),
+            "unsharp" => Ok(Filter::UnsharpMask { mode: SharpenMode::Unsharp, radius: 1.0, amount: 1.0, threshold: 3.0, relative: true }),
+            _ => Err(ImageError::UnknownFilter(name.to_string())),
+        }
+    }
+
+    pub fn name(&self) -> &'static str { // Nome curto do filtro
+        match self {
+            Filter::Grayscale => "grayscale",
+            Filter::Invert => "invert",
+            Filter::Contrast { .. } => "contrast",
+            Filter::Blur { .. } => "blur",
+            Filter::Sharpen => "sharpen",
+            Filter::Edges { .. } => "edges",
+            Filter::Resize { .. } => "resize",
+            Filter::Rotate90Clockwise => "rotate_90_cw",
+            Filter::Rotate90CounterClockwise => "rotate_90_ccw",
+            Filter::Rotate { .. } => "rotate",
+            Filter::FlipHorizontal => "flip_h",
+            Filter::FlipVertical => "flip_v",
+            Filter::Crop { .. } => "crop",
+            Filter::Levels { .. } => "levels",
+            Filter::Curves { .. } => "curves",
+            Filter::Equalize => "equalize",
+            Filter::Clahe { .. } => "clahe",
+            Filter::ExtractChannel { .. } => "channel",
+            Filter::SwapChannels { .. } => "swap_channels",
+            Filter::HueSaturation { .. } => "hue_saturation",
+            Filter::ChannelAdjust { .. } => "channel_adjust",
+            Filter::AutoWhiteBalance { .. } => "white_balance",
+            Filter::Temperature { .. } => "temperature",
+            Filter::NeutralPoint { .. } => "neutral_point",
+            Filter::Median { .. } => "median",
+            Filter::Bilateral { .. } => "bilateral",
+            Filter::NlMeans { .. } => "nlmeans",
+            Filter::UnsharpMask { .. } => "unsharp",
+            Filter::Convolution { .. } => "kernel",
+            Filter::Morphology { .. } => "morphology",
+            Filter::Threshold { .. } => "threshold",
+            Filter::Perspective { .. } => "perspective",
+            Filter::Undistort { .. } => "undistort",
+        }
+    }
+
+    pub fn label(&self) -> &'static str { // Nome mostrado na interface
+        match self {
+            Filter::Grayscale => "Escala de cinza",
+            Filter::Invert => "Inversão de cores",
+            Filter::Contrast { .. } => "Contraste e brilho",
+            Filter::Blur { .. } => "Desfoque (blur)",
+            Filter::Sharpen => "Nitidez (sharpen)",
+            Filter::Edges { .. } => "Detecção de bordas",
+            Filter::Resize { .. } => "Redimensionar",
+            Filter::Rotate90Clockwise => "Rotação de 90 graus no sentido horário",
+            Filter::Rotate90CounterClockwise => "Rotação de 90 graus no sentido anti-horário",
+            Filter::Rotate { .. } => "Rotação livre",
+            Filter::FlipHorizontal => "Espelhar na horizontal",
+            Filter::FlipVertical => "Espelhar na vertical",
+            Filter::Crop { .. } => "Recorte",
+            Filter::Levels { .. } => "Níveis",
+            Filter::Curves { .. } => "Curvas",
+            Filter::Equalize => "Equalização do histograma",
+            Filter::Clahe { .. } => "Contraste local (CLAHE)",
+            Filter::ExtractChannel { .. } => "Ver canal",
+            Filter::SwapChannels { .. } => "Trocar canais",
+            Filter::HueSaturation { .. } => "Matiz, saturação e luminosidade",
+            Filter::ChannelAdjust { .. } => "Ajuste de canal (espaço de cor)",
+            Filter::AutoWhiteBalance { .. } => "Balanço de branco automático",
+            Filter::Temperature { .. } => "Temperatura e matiz",
+            Filter::NeutralPoint { .. } => "Ponto neutro (conta-gotas)",
+            Filter::Median { .. } => "Filtro mediano",
+            Filter::Bilateral { .. } => "Filtro bilateral",
+            Filter::NlMeans { .. } => "Remoção de ruído (non-local means)",
+            Filter::UnsharpMask { .. } => "Máscara de nitidez",
+            Filter::Convolution { .. } => "Kernel personalizado",
+            Filter::Morphology { .. } => "Morfologia",
+            Filter::Threshold { .. } => "Limiarização",
+            Filter::Perspective { .. } => "Perspectiva (documento)",
+            Filter::Undistort { .. } => "Correção de lente",
+        }
+    }
+
+    pub fn params(&self) -> Vec<Param> { // Parâmetros ajustáveis do filtro, com os valores atuais
+        match self {
+            Filter::Contrast { alpha, beta } => vec![
+                Param::new("alpha", "Contraste (alpha)", *alpha, 0.0, 3.0, 0.05),
+                Param::new("beta", "Brilho (beta)", *beta, -100.0, 100.0, 1.0),
+            ],
+            Filter::Blur { kernel_size, sigma } => vec![
+                Param::new("ksize", "Tamanho do kernel", f64::from(*kernel_size), 1.0, 31.0, 2.0),
+                Param::new("sigma", "Sigma", *sigma, 0.0, 20.0, 0.1),
+            ],
+            Filter::Edges { detector } => vec![
+                Param::choice("operator", "Operador", detector.operator.index(), EdgeOperator::OPTIONS),
+                Param::choice("direction", "Direção (Sobel e Scharr)", detector.direction.index(), EdgeDirection::OPTIONS),
+                Param::choice("thresholds", "Limiares (Canny)", usize::from(detector.auto_threshold), &[("manual", "Manuais"), ("auto", "Automáticos (mediana)")]),
+                Param::new("low", "Limiar inferior", detector.low_threshold, 0.0, 500.0, 1.0),
+                Param::new("high", "Limiar superior", detector.high_threshold, 0.0, 500.0, 1.0),
+                Param::new("sigma", "Sigma (limiares automáticos)", detector.sigma, 0.0, 1.0, 0.01),
+                Param::new("aperture", "Abertura (kernel)", f64::from(detector.aperture_size), 1.0, 7.0, 2.0),
+                Param::new("scale", "Ganho (Sobel, Scharr e Laplaciano)", detector.scale, 0.05, 5.0, 0.05),
+                Param::choice("overlay", "Exibição", detector.overlay.index(), EdgeOverlay::OPTIONS),
+            ],
+            Filter::Resize { target, interpolation } => vec![
+                Param::choice("mode", "Modo", target.mode.index(), ResizeMode::OPTIONS),
+                Param::new("scale", "Escala (1 = 100%)", target.factor, 0.05, 4.0, 0.05),
+                Param::new("width", "Largura / lado (px)", f64::from(target.width), 1.0, 8000.0, 1.0),
+                Param::new("height", "Altura (px)", f64::from(target.height), 1.0, 8000.0, 1.0),
+                Param::choice("aspect", "Proporção", usize::from(target.keep_aspect), &[("free", "Livre"), ("lock", "Travada")]),
+                Param::choice("interp", "Interpolação", interpolation.index(), Interpolation::OPTIONS),
+            ],
+            Filter::Rotate { angle, mode, border } => vec![
+                Param::new("angle", "Ângulo (graus)", *angle, -180.0, 180.0, 0.1),
+                Param::choice("mode", "Cantos", mode.index(), RotateMode::OPTIONS),
+                Param::choice("border", "Preenchimento", border.index(), BorderFill::OPTIONS),
+            ],
+            Filter::Crop { x, y, width, height } => vec![
+                Param::new("x", "Esquerda", *x, 0.0, 1.0, 0.001),
+                Param::new("y", "Topo", *y, 0.0, 1.0, 0.001),
+                Param::new("width", "Largura", *width, 0.001, 1.0, 0.001),
+                Param::new("height", "Altura", *height, 0.001, 1.0, 0.001),
+            ],
+            Filter::Perspective { corners } => perspective::CORNER_PARAMS
+                .iter()
+                .map(|(corner, is_x, key, label)| {
+                    let (x, y) = corners[*corner];
+                    Param::new(key, label, if *is_x { x } else { y }, 0.0, 1.0, 0.001)
+                })
+                .collect(),
+            Filter::Undistort { lens, alpha } => vec![
+                Param::new("alpha", "Área (0 = só pixels válidos, 1 = tudo)", *alpha, 0.0, 1.0, 0.01),
+                Param::new("fx", "Distância focal x (÷ largura)", lens.fx, 0.05, 5.0, 0.001),
+                Param::new("fy", "Distância focal y (÷ altura)", lens.fy, 0.05, 5.0, 0.001),
+                Param::new("cx", "Centro óptico x", lens.cx, 0.0, 1.0, 0.001),
+                Param::new("cy", "Centro óptico y", lens.cy, 0.0, 1.0, 0.001),
+                Param::new("k1", "Radial k1", lens.k1, -2.0, 2.0, 0.001),
+                Param::new("k2", "Radial k2", lens.k2, -2.0, 2.0, 0.001),
+                Param::new("p1", "Tangencial p1", lens.p1, -0.1, 0.1, 0.0001),
+                Param::new("p2", "Tangencial p2", lens.p2, -0.1, 0.1, 0.0001),
+                Param::new("k3", "Radial k3", lens.k3, -2.0, 2.0, 0.001),
+            ],
+            Filter::Levels { master, red, green, blue } => vec![
+                Param::new("black", "Ponto preto", master.black, 0.0, 254.0, 1.0),
+                Param::new("white", "Ponto branco", master.white, 1.0, 255.0, 1.0),
+                Param::new("gamma", "Gama", master.gamma, 0.1, 5.0, 0.01),
+                Param::new("red_black", "Vermelho: ponto preto", red.black, 0.0, 254.0, 1.0),
+                Param::new("red_white", "Vermelho: ponto branco", red.white, 1.0, 255.0, 1.0),
+                Param::new("red_gamma", "Vermelho: gama", red.gamma, 0.1, 5.0, 0.01),
+                Param::new("green_black", "Verde: ponto preto", green.black, 0.0, 254.0, 1.0),
+                Param::new("green_white", "Verde: ponto branco", green.white, 1.0, 255.0, 1.0),
+                Param::new("green_gamma", "Verde: gama", green.gamma, 0.1, 5.0, 0.01),
+                Param::new("blue_black", "Azul: ponto preto", blue.black, 0.0, 254.0, 1.0),
+                Param::new("blue_white", "Azul: ponto branco", blue.white, 1.0, 255.0, 1.0),
+                Param::new("blue_gamma", "Azul: gama", blue.gamma, 0.1, 5.0, 0.01),
+            ],
+            Filter::Curves { curves } => tone::CURVE_PARAMS
+                .iter()
+                .map(|(curve, point, is_input, key, label)| {
+                    let (x, y) = curves[*curve].points[*point];
+                    Param::new(key, label, if *is_input { x } else { y }, 0.0, 255.0, 1.0)
+                })
+                .collect(),
+            Filter::Clahe { clip_limit, tile_size } => vec![
+                Param::new("clip", "Limite de contraste", *clip_limit, 0.5, 40.0, 0.1),
+                Param::new("tiles", "Blocos (grade)", f64::from(*tile_size), 2.0, 32.0, 1.0),
+            ],
+            Filter::ExtractChannel { channel } => vec![
+                Param::choice("channel", "Canal", channel.index(), ColorChannel::OPTIONS),
+            ],
+            Filter::SwapChannels { swap } => vec![
+                Param::choice("swap", "Troca", swap.index(), ChannelSwap::OPTIONS),
+            ],
+            Filter::HueSaturation { hue, saturation, lightness } => vec![
+                Param::new("hue", "Matiz (graus)", *hue, -180.0, 180.0, 1.0),
+                Param::new("saturation", "Saturação (%)", *saturation, -100.0, 100.0, 1.0),
+                Param::new("lightness", "Luminosidade (%)", *lightness, -100.0, 100.0, 1.0),
+            ],
+            Filter::ChannelAdjust { space, channel, gain, offset } => vec![
+                Param::choice("space", "Espaço de cor", space.index(), ColorSpace::OPTIONS),
+                Param::choice("channel", "Canal", *channel, ColorSpace::CHANNEL_OPTIONS),
+                Param::new("gain", "Ganho", *gain, 0.0, 3.0, 0.01),
+                Param::new("offset", "Deslocamento", *offset, -128.0, 128.0, 1.0),
+            ],
+            Filter::AutoWhiteBalance { method, percentile } => vec![
+                Param::choice("method", "Método", method.index(), WhiteBalance::OPTIONS),
+                Param::new("percentile", "Percentil do branco", *percentile, 90.0, 100.0, 0.1),
+            ],
+            Filter::Temperature { temperature, tint } => vec![
+                Param::new("temperature", "Temperatura", *temperature, -100.0, 100.0, 1.0),
+                Param::new("tint", "Matiz (verde / magenta)", *tint, -100.0, 100.0, 1.0),
+            ],
+            Filter::NeutralPoint { red, green, blue } => vec![
+                Param::new("red", "Vermelho", *red, 1.0, 255.0, 1.0),
+                Param::new("green", "Verde", *green, 1.0, 255.0, 1.0),
+                Param::new("blue", "Azul", *blue, 1.0, 255.0, 1.0),
+            ],
+            Filter::Median { kernel_size } => vec![
+                Param::new("ksize", "Tamanho do kernel", f64::from(*kernel_size), 3.0, 15.0, 2.0),
+            ],
+            Filter::Bilateral { diameter, sigma_color, sigma_space } => vec![
+                Param::new("diameter", "Diâmetro", f64::from(*diameter), 1.0, 25.0, 1.0),
+                Param::new("sigma_color", "Sigma de cor", *sigma_color, 1.0, 200.0, 1.0),
+                Param::new("sigma_space", "Sigma de espaço", *sigma_space, 1.0, 200.0, 1.0),
+            ],
+            Filter::NlMeans { mode, strength, color_strength, template_size, search_size } => vec![
+                Param::choice("mode", "Variante", mode.index(), NlMeansMode::OPTIONS),
+                Param::new("h", "Força (luminância)", *strength, 1.0, 50.0, 0.5),
+                Param::new("h_color", "Força (cor)", *color_strength, 1.0, 50.0, 0.5),
+                Param::new("template", "Janela do bloco", f64::from(*template_size), 3.0, 11.0, 2.0),
+                Param::new("search", "Janela de busca", f64::from(*search_size), 7.0, 35.0, 2.0),
+            ],
+            Filter::UnsharpMask { mode, radius, amount, threshold, relative } => vec![
+                Param::choice("mode", "Modo", mode.index(), SharpenMode::OPTIONS),
+                Param::new("radius", "Raio", *radius, 0.1, 20.0, 0.1),
+                Param::choice("unit", "Unidade do raio", usize::from(*relative), &[("px", "Pixels"), ("relative", "Por 1000 px do lado maior")]),
+                Param::new("amount", "Intensidade", *amount, 0.0, 5.0, 0.05),
+                Param::new("threshold", "Limiar", *threshold, 0.0, 50.0, 1.0),
+            ],
+            Filter::Convolution { kernel } => {
+                let mut params = vec![
+                    Param::new("rows", "Linhas", kernel.rows as f64, 1.0, KERNEL_MAX as f64, 1.0),
+                    Param::new("cols", "Colunas", kernel.cols as f64, 1.0, KERNEL_MAX as f64, 1.0),
+                    Param::new("divisor", "Divisor (0 = soma)", kernel.divisor, -100.0, 100.0, 1.0),
+                    Param::new("offset", "Deslocamento", kernel.offset, -255.0, 255.0, 1.0),
+                    Param::new("anchor_x", "Âncora: coluna (-1 = centro)", f64::from(kernel.anchor.0), -1.0, (KERNEL_MAX - 1) as f64, 1.0),
+                    Param::new("anchor_y", "Âncora: linha (-1 = centro)", f64::from(kernel.anchor.1), -1.0, (KERNEL_MAX - 1) as f64, 1.0),
+                    Param::choice("border", "Borda", kernel.border.index(), KernelBorder::OPTIONS),
+                ];
+                for (row, keys) in KERNEL_KEYS.iter().enumerate().take(kernel.rows) {
+                    for (col, key) in keys.iter().copied().enumerate().take(kernel.cols) {
+                        params.push(Param::new(key, "Valor do kernel", kernel.values[row][col], -100.0, 100.0, 0.1));
+                    }
+                }
+                params
+            }
+            Filter::Morphology { operation, shape, size, iterations } => vec![
+                Param::choice("operation", "Operação", operation.index(), MorphOperation::OPTIONS),
+                Param::choice("shape", "Elemento", shape.index(), MorphShape::OPTIONS),
+                Param::new("size", "Tamanho do elemento (px)", f64::from(*size), 1.0, 51.0, 1.0),
+                Param::new("iterations", "Repetições", f64::from(*iterations), 1.0, 20.0, 1.0),
+            ],
+            Filter::Threshold { threshold } => vec![
+                Param::choice("method", "Método", threshold.method.index(), ThresholdMethod::OPTIONS),
+                Param::choice("mode", "Tipo", threshold.mode.index(), ThresholdMode::OPTIONS),
+                Param::new("value", "Limiar (método fixo)", threshold.value, 0.0, 255.0, 1.0),
+                Param::new("max", "Valor máximo", threshold.max_value, 0.0, 255.0, 1.0),
+                Param::new("block", "Bloco (adaptativo)", f64::from(threshold.block_size), 3.0, 101.0, 2.0),
+                Param::new("c", "C (adaptativo)", threshold.c, -50.0, 50.0, 1.0),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn set_param(&mut self, key: &str, value: f64) -> Result<()> { // Altera um parâmetro pelo nome curto
+        if !value.is_finite() {
+            return Err(ImageError::InvalidParam(format!("{}={}", key, value)));
+        }
+
+        match (self, key) {
+            (Filter::Contrast { alpha, .. }, "alpha") => *alpha = value,
+            (Filter::Contrast { beta, .. }, "beta") => *beta = value,
+            (Filter::Blur { kernel_size, .. }, "ksize") => *kernel_size = value.round() as i32,
+            (Filter::Blur { sigma, .. }, "sigma") => *sigma = value,
+            (Filter::Edges { detector }, "operator") => detector.operator = EdgeOperator::from_index(option_index(value, EdgeOperator::OPTIONS)),
+            (Filter::Edges { detector }, "direction") => detector.direction = EdgeDirection::from_index(option_index(value, EdgeDirection::OPTIONS)),
+            (Filter::Edges { detector }, "thresholds") => detector.auto_threshold = value.round() != 0.0,
+            (Filter::Edges { detector }, "low") => detector.low_threshold = value,
+            (Filter::Edges { detector }, "high") => detector.high_threshold = value,
+            (Filter::Edges { detector }, "sigma") => detector.sigma = value.clamp(0.0, 1.0),
+            (Filter::Edges { detector }, "aperture") => detector.aperture_size = value.round() as i32,
+            (Filter::Edges { detector }, "scale") => detector.scale = value.max(0.0),
+            (Filter::Edges { detector }, "overlay") => detector.overlay = EdgeOverlay::from_index(option_index(value, EdgeOverlay::OPTIONS)),
+            (Filter::Resize { target, .. }, "mode") => target.mode = ResizeMode::from_index(option_index(value, ResizeMode::OPTIONS)),
+            (Filter::Resize { target, .. }, "scale") => target.factor = value,
+            (Filter::Resize { target, .. }, "percent") => target.factor = value / 100.0,
+            (Filter::Resize { target, .. }, "width") => target.width = value.round() as i32,
+            (Filter::Resize { target, .. }, "height") => target.height = value.round() as i32,
+            (Filter::Resize { target, .. }, "aspect") => target.keep_aspect = value.round() != 0.0,
+            (Filter::Resize { interpolation, .. }, "interp") => *interpolation = Interpolation::from_index(option_index(value, Interpolation::OPTIONS)),
+            (Filter::Rotate { angle, .. }, "angle") => *angle = value,
+            (Filter::Rotate { mode, .. }, "mode") => *mode = RotateMode::from_index(option_index(value, RotateMode::OPTIONS)),
+            (Filter::Rotate { border, .. }, "border") => *border = BorderFill::from_index(option_index(value, BorderFill::OPTIONS)),
+            (Filter::Crop { x, .. }, "x") => *x = value.clamp(0.0, 1.0),
+            (Filter::Crop { y, .. }, "y") => *y = value.clamp(0.0, 1.0),
+            (Filter::Crop { width, .. }, "width") => *width = value.clamp(0.001, 1.0),
+            (Filter::Crop { height, .. }, "height") => *height = value.clamp(0.001, 1.0),
+            (Filter::Levels { master, red, green, blue }, key) => {
+                let (channel, field) = match key.split_once('_') {
+                    Some(("red", field)) => (red, field),
+                    Some(("green", field)) => (green, field),
+                    Some(("blue", field)) => (blue, field),
+                    _ => (master, key),
+                };
+                match field {
+                    "black" => channel.black = value.clamp(0.0, 254.0),
+                    "white" => channel.white = value.clamp(1.0, 255.0),
+                    "gamma" => channel.gamma = value.clamp(0.1, 5.0),
+                    _ => return Err(ImageError::InvalidParam(format!("o filtro levels não tem o parâmetro {}", key))),
+                }
+            }
+            (Filter::Clahe { clip_limit, .. }, "clip") => *clip_limit = value.max(0.1),
+            (Filter::Clahe { tile_size, .. }, "tiles") => *tile_size = (value.round() as i32).clamp(1, 64),
+            (Filter::ExtractChannel { channel }, "channel") => *channel = ColorChannel::from_index(option_index(value, ColorChannel::OPTIONS)),
+            (Filter::SwapChannels { swap }, "swap") => *swap = ChannelSwap::from_index(option_index(value, ChannelSwap::OPTIONS)),
+            (Filter::HueSaturation { hue, .. }, "hue") => *hue = value.clamp(-180.0, 180.0),
+            (Filter::HueSaturation { saturation, .. }, "saturation") => *saturation = value.clamp(-100.0, 100.0),
+            (Filter::HueSaturation { lightness, .. }, "lightness") => *lightness = value.clamp(-100.0, 100.0),
+            (Filter::ChannelAdjust { space, .. }, "space") => *space = ColorSpace::from_index(option_index(value, ColorSpace::OPTIONS)),
+            (Filter::ChannelAdjust { channel, .. }, "channel") => *channel = option_index(value, ColorSpace::CHANNEL_OPTIONS),
+            (Filter::ChannelAdjust { gain, .. }, "gain") => *gain = value.max(0.0),
+            (Filter::ChannelAdjust { offset, .. }, "offset") => *offset = value,
+            (Filter::AutoWhiteBalance { method, .. }, "method") => *method = WhiteBalance::from_index(option_index(value, WhiteBalance::OPTIONS)),
+            (Filter::AutoWhiteBalance { percentile, .. }, "percentile") => *percentile = value.clamp(50.0, 100.0),
+            (Filter::Temperature { temperature, .. }, "temperature") => *temperature = value.clamp(-100.0, 100.0),
+            (Filter::Temperature { tint, .. }, "tint") => *tint = value.clamp(-100.0, 100.0),
+            (Filter::NeutralPoint { red, .. }, "red") => *red = value.clamp(1.0, 255.0),
+            (Filter::NeutralPoint { green, .. }, "green") => *green = value.clamp(1.0, 255.0),
+            (Filter::NeutralPoint { blue, .. }, "blue") => *blue = value.clamp(1.0, 255.0),
+            (Filter::Median { kernel_size }, "ksize") => *kernel_size = value.round() as i32,
+            (Filter::Bilateral { diameter, .. }, "diameter") => *diameter = value.round() as i32,
+            (Filter::Bilateral { sigma_color, .. }, "sigma_color") => *sigma_color = value.max(0.0),
+            (Filter::Bilateral { sigma_space, .. }, "sigma_space") => *sigma_space = value.max(0.0),
+            (Filter::NlMeans { mode, .. }, "mode") => *mode = NlMeansMode::from_index(option_index(value, NlMeansMode::OPTIONS)),
+            (Filter::NlMeans { strength, .. }, "h") => *strength = value.max(0.0),
+            (Filter::NlMeans { color_strength, .. }, "h_color") => *color_strength = value.max(0.0),
+            (Filter::NlMeans { template_size, .. }, "template") => *template_size = value.round() as i32,
+            (Filter::NlMeans { search_size, .. }, "search") => *search_size = value.round() as i32,
+            (Filter::UnsharpMask { mode, .. }, "mode") => *mode = SharpenMode::from_index(option_index(value, SharpenMode::OPTIONS)),
+            (Filter::UnsharpMask { radius, .. }, "radius") => *radius = value.max(0.1),
+            (Filter::UnsharpMask { relative, .. }, "unit") => *relative = value.round() != 0.0,
+            (Filter::UnsharpMask { amount, .. }, "amount") => *amount = value.max(0.0),
+            (Filter::UnsharpMask { threshold, .. }, "threshold") => *threshold = value.max(0.0),
+            (Filter::Convolution { kernel }, "rows") => kernel.rows = (value.round() as usize).clamp(1, KERNEL_MAX),
+            (Filter::Convolution { kernel }, "cols") => kernel.cols = (value.round() as usize).clamp(1, KERNEL_MAX),
+            (Filter::Convolution { kernel }, "divisor") => kernel.divisor = value,
+            (Filter::Convolution { kernel }, "offset") => kernel.offset = value,
+            (Filter::Convolution { kernel }, "anchor_x") => kernel.anchor.0 = (value.round() as i32).clamp(-1, KERNEL_MAX as i32 - 1),
+            (Filter::Convolution { kernel }, "anchor_y") => kernel.anchor.1 = (value.round() as i32).clamp(-1, KERNEL_MAX as i32 - 1),
+            (Filter::Convolution { kernel }, "border") => kernel.border = KernelBorder::from_index(option_index(value, KernelBorder::OPTIONS)),
+            (Filter::Morphology { operation, .. }, "operation") => *operation = MorphOperation::from_index(option_index(value, MorphOperation::OPTIONS)),
+            (Filter::Morphology { shape, .. }, "shape") => *shape = MorphShape::from_index(option_index(value, MorphShape::OPTIONS)),
+            (Filter::Morphology { size, .. }, "size") => *size = (value.round() as i32).clamp(1, 51),
+            (Filter::Morphology { iterations, .. }, "iterations") => *iterations = (value.round() as i32).clamp(1, 20),
+            (Filter::Threshold { threshold }, "method") => threshold.method = ThresholdMethod::from_index(option_index(value, ThresholdMethod::OPTIONS)),
+            (Filter::Threshold { threshold }, "mode") => threshold.mode = ThresholdMode::from_index(option_index(value, ThresholdMode::OPTIONS)),
+            (Filter::Threshold { threshold }, "value") => threshold.value = value.clamp(0.0, 255.0),
+            (Filter::Threshold { threshold }, "max") => threshold.max_value = value.clamp(0.0, 255.0),
+            (Filter::Threshold { threshold }, "block") => threshold.block_size = (value.round() as i32).clamp(3, 101),
+            (Filter::Threshold { threshold }, "c") => threshold.c = value,
+            (Filter::Convolution { kernel }, key) if is_kernel_key(key) => {
+                for (row, keys) in KERNEL_KEYS.iter().enumerate() {
+                    if let Some(col) = keys.iter().position(|kernel_key| *kernel_key == key) {
+                        kernel.values[row][col] = value;
+                    }
+                }
+            }
+            (Filter::Undistort { alpha, .. }, "alpha") => *alpha = value.clamp(0.0, 1.0),
+            (Filter::Undistort { lens, .. }, "fx") => lens.fx = value.max(0.001),
+            (Filter::Undistort { lens, .. }, "fy") => lens.fy = value.max(0.001),
+            (Filter::Undistort { lens, .. }, "cx") => lens.cx = value,
+            (Filter::Undistort { lens, .. }, "cy") => lens.cy = value,
+            (Filter::Undistort { lens, .. }, "k1") => lens.k1 = value,
+            (Filter::Undistort { lens, .. }, "k2") => lens.k2 = value,
+            (Filter::Undistort { lens, .. }, "p1") => lens.p1 = value,
+            (Filter::Undistort { lens, .. }, "p2") => lens.p2 = value,
+            (Filter::Undistort { lens, .. }, "k3") => lens.k3 = value,
+            (Filter::Perspective { corners }, key) => {
+                let (corner, is_x, _, _) = perspective::CORNER_PARAMS
+                    .iter()
+                    .find(|param| param.2 == key)
+                    .ok_or_else(|| ImageError::InvalidParam(format!("o filtro perspective não tem o parâmetro {}", key)))?;
+                if *is_x {
+                    corners[*corner].0 = value.clamp(0.0, 1.0);
+                } else {
+                    corners[*corner].1 = value.clamp(0.0, 1.0);
+                }
+            }
+            (Filter::Curves { curves }, key) => {
+                let (curve, point, is_input, _, _) = tone::CURVE_PARAMS
+                    .iter()
+                    .find(|param| param.3 == key)
+                    .ok_or_else(|| ImageError::InvalidParam(format!("o filtro curves não tem o parâmetro {}", key)))?;
+                if *is_input {
+                    curves[*curve].set_x(*point, value);
+                } else {
+                    curves[*curve].set_y(*point, value);
+                }
+            }
+            (filter, _) => return Err(ImageError::InvalidParam(format!("o filtro {} não tem o parâmetro {}", filter.name(), key))),
+        }
+        Ok(())
+    }
+
+    pub fn is_geometric(&self) -> bool { // Filtros que mudam a posição dos pixels (também são aplicados na transparência)
+        matches!(
+            self,
+            Filter::Resize { .. }
+                | Filter::Rotate90Clockwise
+                | Filter::Rotate90CounterClockwise
+                | Filter::Rotate { .. }
+                | Filter::FlipHorizontal
+                | Filter::FlipVertical
+                | Filter::Crop { .. }
+                | Filter::Perspective { .. }
+                | Filter::Undistort { .. }
+        )
+    }
+
+    pub fn supports_16bit(&self) -> bool { // Filtros calculados direto em 16 bits (os outros passam por 8 bits)
+        self.is_geometric()
+            || matches!(
+                self,
+                Filter::Grayscale
+                    | Filter::Invert
+                    | Filter::Contrast { .. }
+                    | Filter::Blur { .. }
+                    | Filter::Sharpen
+                    | Filter::Levels { .. }
+                    | Filter::Curves { .. }
+                    | Filter::SwapChannels { .. }
+                    | Filter::AutoWhiteBalance { .. }
+                    | Filter::Temperature { .. }
+                    | Filter::NeutralPoint { .. }
+                    | Filter::UnsharpMask { .. }
+                    | Filter::Convolution { .. }
+                    | Filter::Morphology { .. }
+            )
+    }
+
+    pub fn apply(&self, img: &Mat) -> Result<Mat> { // Aplica o filtro mantendo a transparência e a profundidade (8 ou 16 bits) da imagem
+        let (color, alpha) = pixels::split_alpha(img)?;
+        let color = if color.depth() == core::CV_16U && !self.supports_16bit() {
+            pixels::to_16bit(&self.apply_color(&pixels::to_8bit(&color)?)?)?
+        } else {
+            self.apply_color(&color)?
+        };
+
+        match alpha {
+            Some(alpha) if self.is_geometric() => pixels::merge_alpha(&color, &self.apply_color(&alpha)?), // O alfa acompanha o recorte, a rotação etc.
+            Some(alpha) => pixels::merge_alpha(&color, &alpha),
+            None => Ok(color),
+        }
+    }
+
+    fn apply_color(&self, img: &Mat) -> Result<Mat> { // Aplica o filtro na parte colorida (BGR) e devolve uma nova matriz
+        let mut result = Mat::default(); // Cria uma matriz para armazenar o resultado
+
+        match self {
+            Filter::Grayscale => { // Escala de cinza
+                let mut gray = Mat::default();
+                imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+                    .context("conversão para escala de cinza")?;
+                result = color::to_bgr(gray)?; // Continua com 3 canais, para os filtros de cor seguintes
+            }
+            Filter::Invert => { // Inverte as cores
+                core::bitwise_not(img, &mut result, &Mat::default()).context("inversão de cores")?;
+            }
+            Filter::Contrast { alpha, beta } => { // Aumenta o contraste
+                let beta = if img.depth() == core::CV_16U { *beta * 257.0 } else { *beta }; // O brilho é dado na escala de 8 bits
+                img.convert_to(&mut result, -1, *alpha, beta).context("ajuste de contraste")?;
+            }
+            Filter::Blur { kernel_size, sigma } => { // Desfoca a imagem
+                let kernel_size = (*kernel_size).max(1) | 1; // O kernel gaussiano precisa ter tamanho ímpar
+                imgproc::gaussian_blur(img, &mut result, core::Size::new(kernel_size, kernel_size), *sigma, 0.0, core::BORDER_DEFAULT, AlgorithmHint::ALGO_HINT_DEFAULT)
+                    .context("desfoque gaussiano")?;
+            }
+            Filter::Sharpen => { // Aumenta a nitidez
+                let kernel = Mat::from_slice_2d(&[
+                    &[0.0, -1.0, 0.0],
+                    &[-1.0, 5.0, -1.0],
+                    &[0.0, -1.0, 0.0],
+                ]).context("criação do kernel de nitidez")?;
+                imgproc::filter_2d(img, &mut result, -1, &kernel, core::Point::new(-1, -1), 0.0, core::BORDER_DEFAULT)
+                    .context("filtro de nitidez")?;
+            }
+            Filter::Edges { detector } => { // Detecta as bordas
+                result = edges::detect_edges(img, detector)?;
+            }
+            Filter::Resize { target, interpolation } => { // Muda a resolução da imagem (0.5 reduz pela metade)
+                result = geometry::resize(img, target, *interpolation)?;
+            }
+            Filter::Rotate90Clockwise => { // gira a imagem 90 graus no sentido horário
+                core::rotate(img, &mut result, core::ROTATE_90_CLOCKWISE).context("rotação de 90 graus")?;
+            }
+            Filter::Rotate90CounterClockwise => { // gira a imagem 90 graus no sentido anti-horário
+                core::rotate(img, &mut result, core::ROTATE_90_COUNTERCLOCKWISE).context("rotação de 90 graus")?;
+            }
+            Filter::Rotate { angle, mode, border } => { // Gira por qualquer ângulo
+                result = geometry::rotate(img, *angle, *mode, *border)?;
+            }
+            Filter::FlipHorizontal => { // Espelha da esquerda para a direita
+                result = geometry::flip(img, true)?;
+            }
+            Filter::FlipVertical => { // Espelha de cima para baixo
+                result = geometry::flip(img, false)?;
+            }
+            Filter::Crop { x, y, width, height } => { // Recorta o retângulo escolhido na prévia
+                result = geometry::crop(img, *x, *y, *width, *height)?;
+            }
+            Filter::Perspective { corners } => { // Endireita o documento marcado na prévia
+                result = perspective::warp_document(img, corners)?;
+            }
+            Filter::Undistort { lens, alpha } => { // Remove a distorção de barril/almofada da lente
+                result = lens::undistort(img, lens, *alpha)?;
+            }
+            Filter::Levels { master, red, green, blue } => { // Ajusta pontos preto/branco e gama com uma tabela por canal
+                result = tone::levels(img, master, [blue, green, red])?;
+            }
+            Filter::Curves { curves } => { // Curvas de tons convertidas em tabelas de 256 entradas
+                let [master, red, green, blue] = curves;
+                result = tone::curves(img, master, [blue, green, red])?;
+            }
+            Filter::Equalize => { // Espalha os tons por toda a faixa (bom para fotos escuras)
+                result = tone::equalize(img)?;
+            }
+            Filter::Clahe { clip_limit, tile_size } => { // Contraste local, bom para documentos escaneados
+                result = tone::clahe(img, *clip_limit, (*tile_size).max(1))?;
+            }
+            Filter::ExtractChannel { channel } => { // Canal isolado, já reexpandido para 3 canais
+                result = color::extract_channel(img, *channel)?;
+            }
+            Filter::SwapChannels { swap } => {
+                result = color::swap_channels(img, *swap)?;
+            }
+            Filter::HueSaturation { hue, saturation, lightness } => {
+                result = color::hue_saturation(img, *hue, *saturation, *lightness)?;
+            }
+            Filter::ChannelAdjust { space, channel, gain, offset } => {
+                result = color::adjust_channel(img, *space, *channel, *gain, *offset)?;
+            }
+            Filter::AutoWhiteBalance { method, percentile } => { // Corrige a dominante de cor automaticamente
+                result = color::auto_white_balance(img, *method, *percentile)?;
+            }
+            Filter::Temperature { temperature, tint } => {
+                result = color::temperature_tint(img, *temperature, *tint)?;
+            }
+            Filter::NeutralPoint { red, green, blue } => {
+                result = color::neutral_point(img, *red, *green, *blue)?;
+            }
+            Filter::Median { kernel_size } => { // Remove ruído preservando as bordas
+                result = denoise::median(img, *kernel_size)?;
+            }
+            Filter::Bilateral { diameter, sigma_color, sigma_space } => {
+                result = denoise::bilateral(img, *diameter, *sigma_color, *sigma_space)?;
+            }
+            Filter::NlMeans { mode, strength, color_strength, template_size, search_size } => {
+                result = denoise::nl_means(img, *mode, *strength, *color_strength, *template_size, *search_size)?;
+            }
+            Filter::UnsharpMask { mode, radius, amount, threshold, relative } => { // Nitidez ajustável, que acompanha a resolução da imagem
+                result = sharpen::unsharp_mask(img, *mode, *radius, *amount, *threshold, *relative)?;
+            }
+            Filter::Convolution { kernel } => { // Convolução com o kernel da grade
+                result = convolution::convolve(img, kernel)?;
+            }
+            Filter::Morphology { operation, shape, size, iterations } => { // Erosão, dilatação e derivadas
+                result = morphology::morphology(img, *operation, *shape, *size, *iterations)?;
+            }
+            Filter::Threshold { threshold } => { // Imagem binária (preparação para OCR)
+                result = threshold::threshold(img, threshold)?;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub fn load_image(input_image_path: &str) -> Result<Mat> { // Carrega a imagem do disco, sem aplicar a orientação do EXIF (ver metadata::load_oriented)
+    match imgcodecs::imread(input_image_path, imgcodecs::IMREAD_UNCHANGED | imgcodecs::IMREAD_IGNORE_ORIENTATION) { // Mantém a transparência e os 16 bits
+        Ok(mat) if mat.empty() => Err(ImageError::Read(input_image_path.to_string(), None)), // Arquivo inexistente ou formato não reconhecido (Requisito da ponderada)
+        Ok(mat) => {
+            let mat = match mat.depth() {
+                core::CV_8U | core::CV_16U => mat,
+                _ => pixels::to_8bit(&mat)?, // Outras profundidades (ponto flutuante etc.) viram 8 bits
+            };
+            color::to_bgr(mat) // Imagens em cinza passam a ter 3 canais, como as coloridas
+        }
+        Err(e) => Err(ImageError::Read(input_image_path.to_string(), Some(e))), // Erro do OpenCV ao decodificar
+    }
+}
+
+pub fn save_image(output_path: &str, img: &Mat, params: &core::Vector<i32>) -> Result<()> { // Grava a imagem no disco (somente na exportação)
+    match imgcodecs::imwrite(output_path, img, params) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ImageError::Write(output_path.to_string())),
+        Err(e) => Err(anyhow::Error::new(e).context(format!("gravação de {}", output_path)).into()),
+    }
+}
+
+pub fn preview_data_url(img: &Mat) -> Result<String> { // Codifica a imagem em memória como data URL para o <img> da interface
+    let img = &pixels::to_8bit(img)?; // A prévia não precisa dos 16 bits (e fica bem menor)
+    let mut buffer = core::Vector::<u8>::new();
+    let params = core::Vector::from_slice(&[imgcodecs::IMWRITE_PNG_COMPRESSION, 1]); // Compressão baixa: a prévia precisa ser rápida
+    imgcodecs::imencode(".png", img, &mut buffer, &params).context("codificação da prévia")?;
+    Ok(format!("data:image/png;base64,{}", base64_encode(buffer.as_slice())))
+}
+
+fn base64_encode(bytes: &[u8]) -> String { // Codificação base64 simples, usada apenas nas prévias
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b1 = u32::from(chunk[0]);
+        let b2 = u32::from(chunk.get(1).copied().unwrap_or(0));
+        let b3 = u32::from(chunk.get(2).copied().unwrap_or(0));
+        let n = (b1 << 16) | (b2 << 8) | b3;
+
+        encoded.push(ALPHABET[((n >> 18) & 63) as usize] as char);
+        encoded.push(ALPHABET[((n >> 12) & 63) as usize] as char);
+        encoded.push(if chunk.len() > 1 { ALPHABET[((n >> 6) & 63) as usize] as char } else { '=' });
+        encoded.push(if chunk.len() > 2 { ALPHABET[(n & 63) as usize] as char } else { '=' });
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_rfc_4648_vectors() { // Vetores da RFC 4648, cobrindo os três casos de preenchimento
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(base64_encode(input.as_bytes()), expected, "entrada {:?}", input);
+        }
+    }
+
+    #[test]
+    fn filter_text_round_trips_for_every_name() { // parse(display(f)) == f com os valores padrão de todos os filtros
+        for name in Filter::NAMES {
+            let filter = Filter::with_defaults(name).unwrap();
+            let text = filter.to_string();
+            let parsed: Filter = text.parse().unwrap_or_else(|e| panic!("{} não foi lido de volta: {}", text, e));
+            assert_eq!(parsed, filter, "{}", text);
+        }
+    }
+
+    #[test]
+    fn filter_text_accepts_option_names_and_numbers() {
+        let by_name: Filter = "rotate:angle=12.5,mode=expand".parse().unwrap();
+        let by_index: Filter = "rotate:angle=12.5,mode=1".parse().unwrap();
+        assert_eq!(by_name, by_index);
+        assert_eq!(by_name.to_string().parse::<Filter>().unwrap(), by_name);
+    }
+
+    #[test]
+    fn base64_uses_the_last_alphabet_characters() { // Bytes altos caem em '+' e '/'
+        assert_eq!(base64_encode(&[0xFB, 0xFF]), "+/8=");
+        assert_eq!(base64_encode(&[0xFF, 0xFF, 0xFF]), "////");
+        assert_eq!(base64_encode(&[0x00, 0x00, 0x00]), "AAAA");
+    }
+}
@@ -0,0 +1,337 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::pixels::full_scale;
+use super::tone::{apply_lut, channel_lut, histogram};
+use super::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSpace { // Espaços de cor em que os canais podem ser ajustados
+    Hsv,
+    Hls,
+    Lab,
+    YCrCb,
+}
+
+impl ColorSpace {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("hsv", "HSV"),
+        ("hls", "HLS"),
+        ("lab", "Lab"),
+        ("ycrcb", "YCrCb"),
+    ];
+
+    pub const CHANNEL_OPTIONS: &'static [(&'static str, &'static str)] = &[ // Canais pela posição, com o nome em cada espaço
+        ("1", "Canal 1 (H / H / L / Y)"),
+        ("2", "Canal 2 (S / L / a / Cr)"),
+        ("3", "Canal 3 (V / S / b / Cb)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ColorSpace::Hls,
+            2 => ColorSpace::Lab,
+            3 => ColorSpace::YCrCb,
+            _ => ColorSpace::Hsv,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn codes(self) -> (i32, i32) { // Conversões de ida e volta (a partir do BGR)
+        match self {
+            ColorSpace::Hsv => (imgproc::COLOR_BGR2HSV_FULL, imgproc::COLOR_HSV2BGR_FULL),
+            ColorSpace::Hls => (imgproc::COLOR_BGR2HLS_FULL, imgproc::COLOR_HLS2BGR_FULL),
+            ColorSpace::Lab => (imgproc::COLOR_BGR2Lab, imgproc::COLOR_Lab2BGR),
+            ColorSpace::YCrCb => (imgproc::COLOR_BGR2YCrCb, imgproc::COLOR_YCrCb2BGR),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChannel { // Canal mostrado sozinho, em tons de cinza
+    Blue,
+    Green,
+    Red,
+    Hue,
+    Saturation,
+    Value,
+    Lightness,
+    LabA,
+    LabB,
+    Luma,
+    Cr,
+    Cb,
+}
+
+impl ColorChannel {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("blue", "Azul"),
+        ("green", "Verde"),
+        ("red", "Vermelho"),
+        ("hue", "Matiz (HSV)"),
+        ("saturation", "Saturação (HSV)"),
+        ("value", "Valor (HSV)"),
+        ("lightness", "Luminosidade (Lab)"),
+        ("lab_a", "a (Lab, verde-vermelho)"),
+        ("lab_b", "b (Lab, azul-amarelo)"),
+        ("luma", "Y (YCrCb)"),
+        ("cr", "Cr (YCrCb)"),
+        ("cb", "Cb (YCrCb)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ColorChannel::Green,
+            2 => ColorChannel::Red,
+            3 => ColorChannel::Hue,
+            4 => ColorChannel::Saturation,
+            5 => ColorChannel::Value,
+            6 => ColorChannel::Lightness,
+            7 => ColorChannel::LabA,
+            8 => ColorChannel::LabB,
+            9 => ColorChannel::Luma,
+            10 => ColorChannel::Cr,
+            11 => ColorChannel::Cb,
+            _ => ColorChannel::Blue,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn source(self) -> (Option<ColorSpace>, usize) { // Espaço de cor (None = BGR) e posição do canal
+        match self {
+            ColorChannel::Blue => (None, 0),
+            ColorChannel::Green => (None, 1),
+            ColorChannel::Red => (None, 2),
+            ColorChannel::Hue => (Some(ColorSpace::Hsv), 0),
+            ColorChannel::Saturation => (Some(ColorSpace::Hsv), 1),
+            ColorChannel::Value => (Some(ColorSpace::Hsv), 2),
+            ColorChannel::Lightness => (Some(ColorSpace::Lab), 0),
+            ColorChannel::LabA => (Some(ColorSpace::Lab), 1),
+            ColorChannel::LabB => (Some(ColorSpace::Lab), 2),
+            ColorChannel::Luma => (Some(ColorSpace::YCrCb), 0),
+            ColorChannel::Cr => (Some(ColorSpace::YCrCb), 1),
+            ColorChannel::Cb => (Some(ColorSpace::YCrCb), 2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelSwap { // Troca de canais de cor
+    RedBlue,
+    RedGreen,
+    GreenBlue,
+    RotateForward,
+    RotateBackward,
+}
+
+impl ChannelSwap {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("swap_rb", "Trocar vermelho e azul"),
+        ("swap_rg", "Trocar vermelho e verde"),
+        ("swap_gb", "Trocar verde e azul"),
+        ("rotate_rgb", "Girar R → G → B → R"),
+        ("rotate_rbg", "Girar R → B → G → R"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ChannelSwap::RedGreen,
+            2 => ChannelSwap::GreenBlue,
+            3 => ChannelSwap::RotateForward,
+            4 => ChannelSwap::RotateBackward,
+            _ => ChannelSwap::RedBlue,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn order(self) -> [usize; 3] { // Canal de entrada usado em cada posição de saída (B, G, R)
+        match self {
+            ChannelSwap::RedBlue => [2, 1, 0],
+            ChannelSwap::RedGreen => [0, 2, 1],
+            ChannelSwap::GreenBlue => [1, 0, 2],
+            ChannelSwap::RotateForward => [1, 2, 0],
+            ChannelSwap::RotateBackward => [2, 0, 1],
+        }
+    }
+}
+
+pub fn to_bgr(img: Mat) -> Result<Mat> { // Reexpande imagens de um canal para BGR, para os próximos filtros da cadeia continuarem funcionando
+    if img.channels() != 1 {
+        return Ok(img);
+    }
+    let mut result = Mat::default();
+    imgproc::cvt_color(&img, &mut result, imgproc::COLOR_GRAY2BGR, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão de cinza para BGR")?;
+    Ok(result)
+}
+
+fn convert(img: &Mat, code: i32) -> Result<Mat> {
+    let mut result = Mat::default();
+    imgproc::cvt_color(img, &mut result, code, 0, AlgorithmHint::ALGO_HINT_DEFAULT).context("conversão de espaço de cor")?;
+    Ok(result)
+}
+
+fn split(img: &Mat) -> Result<core::Vector<Mat>> {
+    let mut channels = core::Vector::<Mat>::new();
+    core::split(img, &mut channels).context("separação dos canais")?;
+    Ok(channels)
+}
+
+fn merge(channels: &core::Vector<Mat>) -> Result<Mat> {
+    let mut result = Mat::default();
+    core::merge(channels, &mut result).context("junção dos canais")?;
+    Ok(result)
+}
+
+pub fn extract_channel(img: &Mat, channel: ColorChannel) -> Result<Mat> { // Mostra um único canal em tons de cinza
+    let img = to_bgr(img.clone())?;
+    let (space, position) = channel.source();
+    let converted = match space {
+        Some(space) => convert(&img, space.codes().0)?,
+        None => img,
+    };
+    let channel = split(&converted)?.get(position).context("leitura do canal")?;
+    to_bgr(channel)
+}
+
+pub fn swap_channels(img: &Mat, swap: ChannelSwap) -> Result<Mat> { // Troca os canais B, G e R de lugar
+    let channels = split(&to_bgr(img.clone())?)?;
+    let mut swapped = core::Vector::<Mat>::new();
+    for source in swap.order() {
+        swapped.push(channels.get(source).context("leitura do canal")?);
+    }
+    merge(&swapped)
+}
+
+pub fn adjust_channel(img: &Mat, space: ColorSpace, position: usize, gain: f64, offset: f64) -> Result<Mat> { // Multiplica e desloca um canal no espaço de cor escolhido
+    let (forward, backward) = space.codes();
+    let mut channels = split(&convert(&to_bgr(img.clone())?, forward)?)?;
+    let mut adjusted = Mat::default();
+    channels
+        .get(position)
+        .context("leitura do canal")?
+        .convert_to(&mut adjusted, -1, gain, offset)
+        .context("ajuste do canal")?;
+    channels.set(position, adjusted).context("troca do canal")?;
+    convert(&merge(&channels)?, backward)
+}
+
+pub fn hue_saturation(img: &Mat, hue: f64, saturation: f64, lightness: f64) -> Result<Mat> { // Matiz (graus), saturação e luminosidade (%) ajustadas de forma independente, no HLS
+    let hls = convert(&to_bgr(img.clone())?, imgproc::COLOR_BGR2HLS_FULL)?;
+    let shift = hue / 360.0 * 256.0; // No HLS_FULL a matiz vai de 0 a 255
+    let hue_table = channel_lut(|value| (value + shift).rem_euclid(256.0).min(255.0));
+    let lightness = (lightness / 100.0).clamp(-1.0, 1.0);
+    let lightness_table = channel_lut(|value| {
+        if lightness >= 0.0 { value + (255.0 - value) * lightness } else { value * (1.0 + lightness) }
+    });
+    let saturation = (1.0 + saturation / 100.0).max(0.0);
+    let saturation_table = channel_lut(|value| value * saturation);
+
+    let adjusted = apply_lut(&hls, [hue_table, lightness_table, saturation_table])?;
+    convert(&adjusted, imgproc::COLOR_HLS2BGR_FULL)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhiteBalance { // Métodos de balanço de branco automático
+    GrayWorld, // A média da imagem deve ser cinza
+    WhitePatch, // Os pontos mais claros devem ser brancos
+}
+
+impl WhiteBalance {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("gray_world", "Mundo cinza"),
+        ("white_patch", "Ponto branco"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => WhiteBalance::WhitePatch,
+            _ => WhiteBalance::GrayWorld,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+fn apply_gains(img: &Mat, gains: [f64; 3]) -> Result<Mat> { // Multiplica cada canal (B, G, R) pelo seu ganho
+    let mut channels = split(&to_bgr(img.clone())?)?;
+    for (index, gain) in gains.iter().enumerate() {
+        let mut adjusted = Mat::default();
+        channels
+            .get(index)
+            .context("leitura do canal")?
+            .convert_to(&mut adjusted, -1, *gain, 0.0)
+            .context("ganho do canal")?;
+        channels.set(index, adjusted).context("troca do canal")?;
+    }
+    merge(&channels)
+}
+
+pub fn auto_white_balance(img: &Mat, method: WhiteBalance, percentile: f64) -> Result<Mat> { // Remove a dominante de cor automaticamente
+    let img = to_bgr(img.clone())?;
+    let gains = match method {
+        WhiteBalance::GrayWorld => {
+            let mean = core::mean(&img, &Mat::default()).context("média dos canais")?;
+            let gray = (mean[0] + mean[1] + mean[2]) / 3.0;
+            [0, 1, 2].map(|index| gray / mean[index].max(1.0))
+        }
+        WhiteBalance::WhitePatch => {
+            let histogram = histogram(&img)?;
+            let scale = full_scale(&img) / 255.0;
+            [&histogram.blue, &histogram.green, &histogram.red].map(|bins| {
+                let white = percentile_value(bins, percentile).max(1.0) * scale;
+                full_scale(&img) / white
+            })
+        }
+    };
+    apply_gains(&img, gains)
+}
+
+fn percentile_value(bins: &[f32], percentile: f64) -> f64 { // Valor (0 a 255) abaixo do qual fica o percentual dado de pixels
+    let total: f64 = bins.iter().map(|count| f64::from(*count)).sum();
+    let target = total * (percentile / 100.0).clamp(0.0, 1.0);
+    let mut accumulated = 0.0;
+    for (value, count) in bins.iter().enumerate() {
+        accumulated += f64::from(*count);
+        if accumulated >= target {
+            return value as f64;
+        }
+    }
+    255.0
+}
+
+pub fn temperature_tint(img: &Mat, temperature: f64, tint: f64) -> Result<Mat> { // Temperatura (+ mais quente, - mais fria) e matiz (+ magenta, - verde), de -100 a 100
+    let warmth = (temperature / 100.0).clamp(-1.0, 1.0) * 0.3;
+    let magenta = (tint / 100.0).clamp(-1.0, 1.0) * 0.3;
+    apply_gains(img, [1.0 - warmth, 1.0 - magenta, 1.0 + warmth])
+}
+
+pub fn neutral_point(img: &Mat, red: f64, green: f64, blue: f64) -> Result<Mat> { // Torna cinza a cor escolhida com o conta-gotas (valores de 0 a 255)
+    let gray = (red + green + blue) / 3.0;
+    apply_gains(img, [gray / blue.max(1.0), gray / green.max(1.0), gray / red.max(1.0)])
+}
+
+pub fn sample_color(img: &Mat, x: f64, y: f64) -> Result<(f64, f64, f64)> { // Cor média (R, G, B de 0 a 255) numa área de 5x5 pixels no ponto relativo (0 a 1)
+    let img = to_bgr(img.clone())?;
+    let column = ((x.clamp(0.0, 1.0) * f64::from(img.cols())) as i32).clamp(0, img.cols() - 1);
+    let row = ((y.clamp(0.0, 1.0) * f64::from(img.rows())) as i32).clamp(0, img.rows() - 1);
+    let left = (column - 2).max(0);
+    let top = (row - 2).max(0);
+    let area = core::Rect::new(left, top, 5.min(img.cols() - left), 5.min(img.rows() - top));
+
+    let sample = Mat::roi(&img, area).context("área do conta-gotas")?.try_clone().context("cópia da área do conta-gotas")?;
+    let mean = core::mean(&sample, &Mat::default()).context("cor do conta-gotas")?;
+    let scale = 255.0 / full_scale(&img);
+    Ok((mean[2] * scale, mean[1] * scale, mean[0] * scale))
+}
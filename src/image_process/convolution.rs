@@ -0,0 +1,124 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*};
+
+use super::Result;
+
+pub const KERNEL_MAX: usize = 7; // Tamanho máximo do kernel (linhas e colunas)
+
+pub const KERNEL_KEYS: [[&str; KERNEL_MAX]; KERNEL_MAX] = [
+    ["k0_0", "k0_1", "k0_2", "k0_3", "k0_4", "k0_5", "k0_6"],
+    ["k1_0", "k1_1", "k1_2", "k1_3", "k1_4", "k1_5", "k1_6"],
+    ["k2_0", "k2_1", "k2_2", "k2_3", "k2_4", "k2_5", "k2_6"],
+    ["k3_0", "k3_1", "k3_2", "k3_3", "k3_4", "k3_5", "k3_6"],
+    ["k4_0", "k4_1", "k4_2", "k4_3", "k4_4", "k4_5", "k4_6"],
+    ["k5_0", "k5_1", "k5_2", "k5_3", "k5_4", "k5_5", "k5_6"],
+    ["k6_0", "k6_1", "k6_2", "k6_3", "k6_4", "k6_5", "k6_6"],
+];
+
+pub fn is_kernel_key(key: &str) -> bool { // Parâmetros dos valores do kernel (editados na grade, não em sliders)
+    KERNEL_KEYS.iter().flatten().any(|kernel_key| *kernel_key == key)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KernelBorder { // Como os pixels fora da imagem são tratados na convolução
+    Reflect101,
+    Replicate,
+    Reflect,
+    Constant,
+}
+
+impl KernelBorder {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("reflect_101", "Espelhar (padrão)"),
+        ("replicate", "Repetir borda"),
+        ("reflect", "Espelhar com a borda"),
+        ("constant", "Preto"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => KernelBorder::Replicate,
+            2 => KernelBorder::Reflect,
+            3 => KernelBorder::Constant,
+            _ => KernelBorder::Reflect101,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn flag(self) -> i32 {
+        match self {
+            KernelBorder::Reflect101 => core::BORDER_REFLECT_101,
+            KernelBorder::Replicate => core::BORDER_REPLICATE,
+            KernelBorder::Reflect => core::BORDER_REFLECT,
+            KernelBorder::Constant => core::BORDER_CONSTANT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kernel { // Kernel de convolução definido pelo usuário
+    pub rows: usize,
+    pub cols: usize,
+    pub values: [[f64; KERNEL_MAX]; KERNEL_MAX], // Só as primeiras `rows` x `cols` posições são usadas
+    pub divisor: f64, // 0 = soma dos valores (ou 1, se a soma for 0)
+    pub offset: f64, // Somado ao resultado (escala de 8 bits)
+    pub anchor: (i32, i32), // Coluna e linha do ponto de referência (-1 = centro)
+    pub border: KernelBorder,
+}
+
+pub const KERNEL_EXAMPLES: &[(&str, [[f64; 3]; 3])] = &[ // Kernels 3x3 prontos, para começar a experimentar
+    ("Identidade", [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]]),
+    ("Média", [[1.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 1.0]]),
+    ("Gaussiano", [[1.0, 2.0, 1.0], [2.0, 4.0, 2.0], [1.0, 2.0, 1.0]]),
+    ("Nitidez", [[0.0, -1.0, 0.0], [-1.0, 5.0, -1.0], [0.0, -1.0, 0.0]]),
+    ("Relevo", [[-2.0, -1.0, 0.0], [-1.0, 1.0, 1.0], [0.0, 1.0, 2.0]]),
+    ("Contorno", [[-1.0, -1.0, -1.0], [-1.0, 8.0, -1.0], [-1.0, -1.0, -1.0]]),
+    ("Sobel horizontal", [[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]]),
+];
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Kernel::example(0)
+    }
+}
+
+impl Kernel {
+    pub fn example(index: usize) -> Self { // Kernel 3x3 a partir dos exemplos
+        let (_, example) = KERNEL_EXAMPLES[index.min(KERNEL_EXAMPLES.len() - 1)];
+        let mut values = [[0.0; KERNEL_MAX]; KERNEL_MAX];
+        for (row, line) in example.iter().enumerate() {
+            values[row][..3].copy_from_slice(line);
+        }
+        Kernel { rows: 3, cols: 3, values, divisor: 0.0, offset: 0.0, anchor: (-1, -1), border: KernelBorder::Reflect101 }
+    }
+
+    fn effective_divisor(&self) -> f64 {
+        if self.divisor != 0.0 {
+            return self.divisor;
+        }
+        let sum: f64 = self.values[..self.rows].iter().map(|line| line[..self.cols].iter().sum::<f64>()).sum();
+        if sum.abs() < 1e-9 { 1.0 } else { sum }
+    }
+}
+
+pub fn convolve(img: &Mat, kernel: &Kernel) -> Result<Mat> { // Convolução com filter_2d (generaliza o kernel fixo do "sharpen")
+    let divisor = kernel.effective_divisor();
+    let lines: Vec<Vec<f64>> = kernel.values[..kernel.rows]
+        .iter()
+        .map(|line| line[..kernel.cols].iter().map(|value| value / divisor).collect())
+        .collect();
+    let matrix = Mat::from_slice_2d(&lines).context("criação do kernel")?;
+
+    let anchor = core::Point::new(
+        if kernel.anchor.0 < 0 { -1 } else { kernel.anchor.0.min(kernel.cols as i32 - 1) },
+        if kernel.anchor.1 < 0 { -1 } else { kernel.anchor.1.min(kernel.rows as i32 - 1) },
+    );
+    let offset = if img.depth() == core::CV_16U { kernel.offset * 257.0 } else { kernel.offset };
+
+    let mut result = Mat::default();
+    imgproc::filter_2d(img, &mut result, -1, &matrix, anchor, offset, kernel.border.flag()).context("convolução personalizada")?;
+    Ok(result)
+}
@@ -0,0 +1,58 @@
+use anyhow::Context;
+use opencv::{core, imgproc, photo, prelude::*};
+
+use super::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NlMeansMode { // Variante do non-local means
+    Colored, // Trata luminância e cor separadamente (fotos coloridas)
+    Gray, // Mesma força em todos os canais (imagens em tons de cinza)
+}
+
+impl NlMeansMode {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("colored", "Colorida"),
+        ("gray", "Tons de cinza"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => NlMeansMode::Gray,
+            _ => NlMeansMode::Colored,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+pub fn median(img: &Mat, kernel_size: i32) -> Result<Mat> { // Mediana: remove ruído "sal e pimenta" sem borrar as bordas
+    let mut result = Mat::default();
+    imgproc::median_blur(img, &mut result, kernel_size.max(3) | 1).context("filtro mediano")?; // O kernel precisa ser ímpar
+    Ok(result)
+}
+
+pub fn bilateral(img: &Mat, diameter: i32, sigma_color: f64, sigma_space: f64) -> Result<Mat> { // Suaviza áreas uniformes e preserva as bordas
+    let mut result = Mat::default();
+    imgproc::bilateral_filter(img, &mut result, diameter.clamp(1, 25), sigma_color, sigma_space, core::BORDER_DEFAULT)
+        .context("filtro bilateral")?;
+    Ok(result)
+}
+
+pub fn nl_means(img: &Mat, mode: NlMeansMode, strength: f64, color_strength: f64, template_size: i32, search_size: i32) -> Result<Mat> { // Non-local means: compara blocos parecidos da imagem inteira
+    let template_size = template_size.max(3) | 1; // As janelas precisam ser ímpares
+    let search_size = search_size.max(template_size + 2) | 1;
+    let mut result = Mat::default();
+    match mode {
+        NlMeansMode::Colored => {
+            photo::fast_nl_means_denoising_colored(img, &mut result, strength as f32, color_strength as f32, template_size, search_size)
+                .context("remoção de ruído (non-local means colorido)")?;
+        }
+        NlMeansMode::Gray => {
+            photo::fast_nl_means_denoising(img, &mut result, strength as f32, template_size, search_size)
+                .context("remoção de ruído (non-local means)")?;
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,223 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::{color, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeOperator { // Operador usado para encontrar as bordas
+    Canny,
+    Sobel,
+    Scharr,
+    Laplacian,
+}
+
+impl EdgeOperator {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("canny", "Canny"),
+        ("sobel", "Sobel"),
+        ("scharr", "Scharr"),
+        ("laplacian", "Laplaciano"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => EdgeOperator::Sobel,
+            2 => EdgeOperator::Scharr,
+            3 => EdgeOperator::Laplacian,
+            _ => EdgeOperator::Canny,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeDirection { // Direção da derivada (Sobel e Scharr)
+    Magnitude, // Combina as duas direções
+    X, // Bordas verticais
+    Y, // Bordas horizontais
+}
+
+impl EdgeDirection {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("magnitude", "Magnitude (x e y)"),
+        ("x", "Horizontal (x)"),
+        ("y", "Vertical (y)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => EdgeDirection::X,
+            2 => EdgeDirection::Y,
+            _ => EdgeDirection::Magnitude,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeOverlay { // Mostra só as bordas ou desenha as bordas coloridas sobre a original
+    None,
+    Red,
+    Green,
+    Cyan,
+    Yellow,
+}
+
+impl EdgeOverlay {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("none", "Só as bordas"),
+        ("red", "Sobre a original (vermelho)"),
+        ("green", "Sobre a original (verde)"),
+        ("cyan", "Sobre a original (ciano)"),
+        ("yellow", "Sobre a original (amarelo)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => EdgeOverlay::Red,
+            2 => EdgeOverlay::Green,
+            3 => EdgeOverlay::Cyan,
+            4 => EdgeOverlay::Yellow,
+            _ => EdgeOverlay::None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn color(self) -> Option<core::Scalar> { // Cor em BGR
+        match self {
+            EdgeOverlay::None => None,
+            EdgeOverlay::Red => Some(core::Scalar::new(0.0, 0.0, 255.0, 0.0)),
+            EdgeOverlay::Green => Some(core::Scalar::new(0.0, 255.0, 0.0, 0.0)),
+            EdgeOverlay::Cyan => Some(core::Scalar::new(255.0, 255.0, 0.0, 0.0)),
+            EdgeOverlay::Yellow => Some(core::Scalar::new(0.0, 255.0, 255.0, 0.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeDetector { // Configuração do filtro de bordas
+    pub operator: EdgeOperator,
+    pub direction: EdgeDirection,
+    pub low_threshold: f64, // Limiares do Canny (ignorados no modo automático)
+    pub high_threshold: f64,
+    pub auto_threshold: bool, // Limiares calculados a partir da mediana da imagem
+    pub sigma: f64, // Distância dos limiares automáticos até a mediana (0.33 = ±33%)
+    pub aperture_size: i32, // Tamanho do kernel da derivada
+    pub scale: f64, // Ganho aplicado à resposta do Sobel, Scharr e Laplaciano
+    pub overlay: EdgeOverlay,
+}
+
+impl Default for EdgeDetector {
+    fn default() -> Self { // Mesmo resultado do filtro de bordas original: Canny(100, 200, 3)
+        EdgeDetector {
+            operator: EdgeOperator::Canny,
+            direction: EdgeDirection::Magnitude,
+            low_threshold: 100.0,
+            high_threshold: 200.0,
+            auto_threshold: false,
+            sigma: 0.33,
+            aperture_size: 3,
+            scale: 1.0,
+            overlay: EdgeOverlay::None,
+        }
+    }
+}
+
+fn median(gray: &Mat) -> Result<f64> { // Mediana dos tons de uma imagem de 8 bits
+    let mut counts = [0usize; 256];
+    for value in gray.data_bytes().context("leitura dos pixels")? {
+        counts[usize::from(*value)] += 1;
+    }
+    let half = gray.total() / 2;
+    let mut seen = 0;
+    for (value, count) in counts.iter().enumerate() {
+        seen += count;
+        if seen > half {
+            return Ok(value as f64);
+        }
+    }
+    Ok(255.0)
+}
+
+fn auto_thresholds(gray: &Mat, sigma: f64) -> Result<(f64, f64)> { // Limiares do Canny em torno da mediana
+    let median = median(gray)?;
+    let sigma = sigma.clamp(0.0, 1.0);
+    Ok((((1.0 - sigma) * median).max(0.0), ((1.0 + sigma) * median).min(255.0)))
+}
+
+fn derivative(gray: &Mat, operator: EdgeOperator, dx: i32, dy: i32, aperture_size: i32) -> Result<Mat> { // Derivada em ponto flutuante (preserva o sinal)
+    let mut result = Mat::default();
+    if operator == EdgeOperator::Scharr {
+        imgproc::scharr(gray, &mut result, core::CV_32F, dx, dy, 1.0, 0.0, core::BORDER_DEFAULT).context("derivada de Scharr")?;
+    } else {
+        imgproc::sobel(gray, &mut result, core::CV_32F, dx, dy, aperture_size, 1.0, 0.0, core::BORDER_DEFAULT).context("derivada de Sobel")?;
+    }
+    Ok(result)
+}
+
+fn edge_map(gray: &Mat, detector: &EdgeDetector) -> Result<Mat> { // Intensidade das bordas em 8 bits, um canal
+    let mut result = Mat::default();
+    match detector.operator {
+        EdgeOperator::Canny => {
+            let (low, high) = if detector.auto_threshold {
+                auto_thresholds(gray, detector.sigma)?
+            } else {
+                (detector.low_threshold, detector.high_threshold)
+            };
+            let aperture_size = detector.aperture_size.clamp(3, 7) | 1; // O Canny só aceita 3, 5 ou 7
+            imgproc::canny(gray, &mut result, low, high, aperture_size, false).context("detecção de bordas (Canny)")?;
+        }
+        EdgeOperator::Sobel | EdgeOperator::Scharr => {
+            let aperture_size = detector.aperture_size.clamp(1, 7) | 1; // O Sobel aceita 1, 3, 5 ou 7
+            let response = match detector.direction {
+                EdgeDirection::X => derivative(gray, detector.operator, 1, 0, aperture_size)?,
+                EdgeDirection::Y => derivative(gray, detector.operator, 0, 1, aperture_size)?,
+                EdgeDirection::Magnitude => {
+                    let dx = derivative(gray, detector.operator, 1, 0, aperture_size)?;
+                    let dy = derivative(gray, detector.operator, 0, 1, aperture_size)?;
+                    let mut magnitude = Mat::default();
+                    core::magnitude(&dx, &dy, &mut magnitude).context("magnitude do gradiente")?;
+                    magnitude
+                }
+            };
+            core::convert_scale_abs(&response, &mut result, detector.scale, 0.0).context("conversão das bordas para 8 bits")?;
+        }
+        EdgeOperator::Laplacian => {
+            let mut response = Mat::default();
+            imgproc::laplacian(gray, &mut response, core::CV_32F, detector.aperture_size.clamp(1, 7) | 1, 1.0, 0.0, core::BORDER_DEFAULT)
+                .context("detecção de bordas (Laplaciano)")?;
+            core::convert_scale_abs(&response, &mut result, detector.scale, 0.0).context("conversão das bordas para 8 bits")?;
+        }
+    }
+    Ok(result)
+}
+
+pub fn detect_edges(img: &Mat, detector: &EdgeDetector) -> Result<Mat> { // Bordas em tons de cinza (BGR) ou coloridas sobre a original
+    let mut gray = Mat::default();
+    imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para escala de cinza")?;
+    let edges = edge_map(&gray, detector)?;
+
+    let Some(color) = detector.overlay.color() else {
+        return color::to_bgr(edges);
+    };
+
+    let mut strength = Mat::default(); // Bordas fortes ficam com a cor cheia, fracas deixam a original aparecer
+    edges.convert_to(&mut strength, core::CV_32F, 1.0, 0.0).context("peso das bordas")?;
+    let mut background = Mat::default();
+    edges.convert_to(&mut background, core::CV_32F, -1.0, 255.0).context("peso da imagem original")?;
+    let paint = Mat::new_rows_cols_with_default(img.rows(), img.cols(), img.typ(), color).context("criação da cor das bordas")?;
+
+    let mut result = Mat::default();
+    imgproc::blend_linear(&paint, img, &strength, &background, &mut result).context("bordas sobre a imagem original")?;
+    Ok(result)
+}
@@ -0,0 +1,273 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*};
+
+use super::{ImageError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RotateMode { // O que fazer com os cantos na rotação livre
+    Keep, // Mantém o tamanho da imagem (os cantos saem)
+    Expand, // Aumenta a tela para caber a imagem inteira
+    Crop, // Recorta o maior retângulo sem bordas vazias
+}
+
+impl RotateMode {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("keep", "Manter tamanho"),
+        ("expand", "Expandir tela"),
+        ("crop", "Recortar bordas"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => RotateMode::Expand,
+            2 => RotateMode::Crop,
+            _ => RotateMode::Keep,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderFill { // Preenchimento das áreas que ficam fora da imagem
+    Black,
+    White,
+    Replicate,
+    Reflect,
+}
+
+impl BorderFill {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("black", "Preto"),
+        ("white", "Branco"),
+        ("replicate", "Repetir borda"),
+        ("reflect", "Espelhar"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => BorderFill::White,
+            2 => BorderFill::Replicate,
+            3 => BorderFill::Reflect,
+            _ => BorderFill::Black,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn border_mode(self) -> (i32, core::Scalar) { // Modo de borda do OpenCV e a cor usada no modo constante
+        match self {
+            BorderFill::Black => (core::BORDER_CONSTANT, core::Scalar::all(0.0)),
+            BorderFill::White => (core::BORDER_CONSTANT, core::Scalar::all(255.0)),
+            BorderFill::Replicate => (core::BORDER_REPLICATE, core::Scalar::default()),
+            BorderFill::Reflect => (core::BORDER_REFLECT_101, core::Scalar::default()),
+        }
+    }
+}
+
+fn largest_rotated_rect(width: f64, height: f64, angle: f64) -> (f64, f64) { // Maior retângulo alinhado que cabe dentro da imagem girada
+    if width <= 0.0 || height <= 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let width_is_longer = width >= height;
+    let (side_long, side_short) = if width_is_longer { (width, height) } else { (height, width) };
+    let radians = angle.to_radians();
+    let (sin_a, cos_a) = (radians.sin().abs(), radians.cos().abs());
+
+    if side_short <= 2.0 * sin_a * cos_a * side_long || (sin_a - cos_a).abs() < 1e-10 {
+        let x = 0.5 * side_short; // Meio-encaixe: dois cantos tocam o lado maior
+        if width_is_longer { (x / sin_a, x / cos_a) } else { (x / cos_a, x / sin_a) }
+    } else {
+        let cos_2a = cos_a * cos_a - sin_a * sin_a; // Encaixe completo: os quatro cantos tocam os lados
+        ((width * cos_a - height * sin_a) / cos_2a, (height * cos_a - width * sin_a) / cos_2a)
+    }
+}
+
+pub fn rotate(img: &Mat, angle: f64, mode: RotateMode, border: BorderFill) -> Result<Mat> { // Rotação por qualquer ângulo (positivo = anti-horário)
+    let (width, height) = (f64::from(img.cols()), f64::from(img.rows()));
+    let center = core::Point2f::new((width / 2.0) as f32, (height / 2.0) as f32);
+    let mut matrix = imgproc::get_rotation_matrix_2d(center, angle, 1.0).context("matriz de rotação")?;
+
+    let size = if mode == RotateMode::Expand {
+        let radians = angle.to_radians();
+        let (sin_a, cos_a) = (radians.sin().abs(), radians.cos().abs());
+        let new_width = height * sin_a + width * cos_a;
+        let new_height = height * cos_a + width * sin_a;
+        *matrix.at_2d_mut::<f64>(0, 2).context("ajuste da matriz de rotação")? += new_width / 2.0 - width / 2.0; // Recentraliza na tela maior
+        *matrix.at_2d_mut::<f64>(1, 2).context("ajuste da matriz de rotação")? += new_height / 2.0 - height / 2.0;
+        core::Size::new(new_width.round() as i32, new_height.round() as i32)
+    } else {
+        img.size().context("tamanho da imagem")?
+    };
+
+    let (border_mode, border_value) = border.border_mode();
+    let mut rotated = Mat::default();
+    imgproc::warp_affine(img, &mut rotated, &matrix, size, imgproc::INTER_LINEAR, border_mode, border_value)
+        .context("rotação livre")?;
+
+    if mode != RotateMode::Crop {
+        return Ok(rotated);
+    }
+    let (crop_width, crop_height) = largest_rotated_rect(width, height, angle);
+    let crop_width = (crop_width.floor() as i32).clamp(1, img.cols());
+    let crop_height = (crop_height.floor() as i32).clamp(1, img.rows());
+    let rect = core::Rect::new((img.cols() - crop_width) / 2, (img.rows() - crop_height) / 2, crop_width, crop_height);
+    Ok(Mat::roi(&rotated, rect).context("recorte após a rotação")?.try_clone().context("cópia do recorte")?)
+}
+
+pub fn flip(img: &Mat, horizontal: bool) -> Result<Mat> { // Espelha a imagem na horizontal ou na vertical
+    let mut result = Mat::default();
+    core::flip(img, &mut result, if horizontal { 1 } else { 0 }).context("espelhamento")?;
+    Ok(result)
+}
+
+pub fn crop(img: &Mat, x: f64, y: f64, width: f64, height: f64) -> Result<Mat> { // Recorte com coordenadas relativas (0 a 1), independente da resolução
+    let (cols, rows) = (f64::from(img.cols()), f64::from(img.rows()));
+    let left = (x.clamp(0.0, 1.0) * cols).round() as i32;
+    let top = (y.clamp(0.0, 1.0) * rows).round() as i32;
+    let right = ((x + width).clamp(0.0, 1.0) * cols).round() as i32;
+    let bottom = ((y + height).clamp(0.0, 1.0) * rows).round() as i32;
+
+    let rect = core::Rect::new(
+        left.min(img.cols() - 1),
+        top.min(img.rows() - 1),
+        (right - left).max(1).min(img.cols() - left.min(img.cols() - 1)),
+        (bottom - top).max(1).min(img.rows() - top.min(img.rows() - 1)),
+    );
+    Ok(Mat::roi(img, rect).context("recorte")?.try_clone().context("cópia do recorte")?)
+}
+
+pub fn straighten_angle(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 { // Ângulo que deixa a linha desenhada na horizontal (ou vertical, se estiver mais perto disso)
+    let mut angle = (y2 - y1).atan2(x2 - x1).to_degrees();
+    while angle > 45.0 {
+        angle -= 90.0;
+    }
+    while angle <= -45.0 {
+        angle += 90.0;
+    }
+    angle
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResizeMode { // Como o tamanho final é calculado
+    Scale, // Fator de escala (0.5 = metade)
+    Exact, // Largura e altura em pixels
+    LongEdge, // Lado maior com o tamanho dado (a largura)
+    ShortEdge, // Lado menor com o tamanho dado (a largura)
+    Fit, // Cabe dentro da caixa largura x altura, sem distorcer
+}
+
+impl ResizeMode {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("scale", "Porcentagem"),
+        ("exact", "Largura e altura"),
+        ("long_edge", "Lado maior"),
+        ("short_edge", "Lado menor"),
+        ("fit", "Caber na caixa"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ResizeMode::Exact,
+            2 => ResizeMode::LongEdge,
+            3 => ResizeMode::ShortEdge,
+            4 => ResizeMode::Fit,
+            _ => ResizeMode::Scale,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation { // Método de interpolação do redimensionamento
+    Nearest,
+    Linear,
+    Cubic,
+    Area,
+    Lanczos,
+}
+
+impl Interpolation {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("nearest", "Vizinho mais próximo"),
+        ("linear", "Bilinear"),
+        ("cubic", "Bicúbica"),
+        ("area", "Área (melhor para reduzir)"),
+        ("lanczos", "Lanczos4"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            0 => Interpolation::Nearest,
+            2 => Interpolation::Cubic,
+            3 => Interpolation::Area,
+            4 => Interpolation::Lanczos,
+            _ => Interpolation::Linear,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn flag(self) -> i32 {
+        match self {
+            Interpolation::Nearest => imgproc::INTER_NEAREST,
+            Interpolation::Linear => imgproc::INTER_LINEAR,
+            Interpolation::Cubic => imgproc::INTER_CUBIC,
+            Interpolation::Area => imgproc::INTER_AREA,
+            Interpolation::Lanczos => imgproc::INTER_LANCZOS4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeTarget { // Parâmetros do redimensionamento
+    pub mode: ResizeMode,
+    pub factor: f64,
+    pub width: i32,
+    pub height: i32,
+    pub keep_aspect: bool, // No modo Exact, a altura é calculada pela largura
+}
+
+impl ResizeTarget {
+    pub fn size_for(&self, cols: i32, rows: i32) -> Result<core::Size> { // Tamanho final para uma imagem cols x rows
+        let (cols_f, rows_f) = (f64::from(cols), f64::from(rows));
+        let (width, height) = (f64::from(self.width), f64::from(self.height));
+        let scaled = |scale: f64| ((cols_f * scale).round(), (rows_f * scale).round());
+
+        let (new_width, new_height) = match self.mode {
+            ResizeMode::Scale => {
+                if self.factor <= 0.0 {
+                    return Err(ImageError::InvalidParam(format!("escala {} precisa ser positiva", self.factor)));
+                }
+                scaled(self.factor)
+            }
+            ResizeMode::Exact if self.keep_aspect => scaled(width / cols_f),
+            ResizeMode::Exact => (width, height),
+            ResizeMode::LongEdge => scaled(width / cols_f.max(rows_f)),
+            ResizeMode::ShortEdge => scaled(width / cols_f.min(rows_f)),
+            ResizeMode::Fit => scaled((width / cols_f).min(height / rows_f)),
+        };
+
+        if new_width < 1.0 || new_height < 1.0 || !new_width.is_finite() || !new_height.is_finite() {
+            return Err(ImageError::InvalidParam(format!("tamanho final inválido: {}x{}", new_width, new_height)));
+        }
+        Ok(core::Size::new(new_width as i32, new_height as i32))
+    }
+}
+
+pub fn resize(img: &Mat, target: &ResizeTarget, interpolation: Interpolation) -> Result<Mat> { // Redimensiona para o tamanho calculado pelo modo escolhido
+    let size = target.size_for(img.cols(), img.rows())?;
+    let mut result = Mat::default();
+    imgproc::resize(img, &mut result, size, 0.0, 0.0, interpolation.flag()).context("redimensionamento")?;
+    Ok(result)
+}
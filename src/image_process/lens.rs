@@ -0,0 +1,118 @@
+use anyhow::Context;
+use opencv::{calib3d, core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::pixels::{split_alpha, to_8bit};
+use super::{ImageError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LensProfile { // Intrínsecos e distorção de uma lente, relativos ao tamanho da imagem (valem em qualquer resolução)
+    pub fx: f64, // Distância focal em x, dividida pela largura
+    pub fy: f64, // Distância focal em y, dividida pela altura
+    pub cx: f64, // Centro óptico (0 a 1)
+    pub cy: f64,
+    pub k1: f64, // Distorção radial (barril < 0 < almofada)
+    pub k2: f64,
+    pub p1: f64, // Distorção tangencial
+    pub p2: f64,
+    pub k3: f64,
+}
+
+impl Default for LensProfile {
+    fn default() -> Self { // Lente sem distorção (o filtro não muda a imagem)
+        LensProfile { fx: 1.0, fy: 1.0, cx: 0.5, cy: 0.5, k1: 0.0, k2: 0.0, p1: 0.0, p2: 0.0, k3: 0.0 }
+    }
+}
+
+impl LensProfile {
+    fn camera_matrix(&self, cols: i32, rows: i32) -> Result<Mat> { // Matriz da câmera em pixels para o tamanho da imagem
+        let (width, height) = (f64::from(cols), f64::from(rows));
+        Ok(Mat::from_slice_2d(&[
+            [self.fx * width, 0.0, self.cx * width],
+            [0.0, self.fy * height, self.cy * height],
+            [0.0, 0.0, 1.0],
+        ])
+        .context("criação da matriz da câmera")?)
+    }
+
+    fn distortion(&self) -> Result<Mat> {
+        Ok(Mat::from_slice(&[self.k1, self.k2, self.p1, self.p2, self.k3]).context("coeficientes de distorção")?.try_clone().context("cópia dos coeficientes")?)
+    }
+}
+
+pub fn find_chessboard(img: &Mat, board: (i32, i32)) -> Result<Option<core::Vector<core::Point2f>>> { // Cantos internos do tabuleiro (None se não aparecer inteiro na foto)
+    let (color, _) = split_alpha(img)?;
+    let color = to_8bit(&color)?;
+    let mut gray = Mat::default();
+    imgproc::cvt_color(&color, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para escala de cinza")?;
+
+    let pattern = core::Size::new(board.0, board.1);
+    let mut corners = core::Vector::<core::Point2f>::new();
+    let flags = calib3d::CALIB_CB_ADAPTIVE_THRESH | calib3d::CALIB_CB_NORMALIZE_IMAGE | calib3d::CALIB_CB_FAST_CHECK;
+    if !calib3d::find_chessboard_corners(&gray, pattern, &mut corners, flags).context("busca do tabuleiro")? {
+        return Ok(None);
+    }
+
+    let criteria = core::TermCriteria::new(core::TermCriteria_COUNT + core::TermCriteria_EPS, 30, 0.001).context("critério de parada")?;
+    imgproc::corner_sub_pix(&gray, &mut corners, core::Size::new(11, 11), core::Size::new(-1, -1), criteria)
+        .context("refinamento dos cantos")?;
+    Ok(Some(corners))
+}
+
+pub fn calibrate(views: &[core::Vector<core::Point2f>], image_size: (i32, i32), board: (i32, i32)) -> Result<(LensProfile, f64)> { // Intrínsecos e distorção a partir dos cantos encontrados; devolve também o erro médio (px)
+    if views.len() < 3 {
+        return Err(ImageError::InvalidParam(format!("a calibração precisa de pelo menos 3 fotos com o tabuleiro (encontrado em {})", views.len())));
+    }
+
+    let square: core::Vector<core::Point3f> = (0..board.1) // O tamanho real do quadrado não muda os intrínsecos
+        .flat_map(|row| (0..board.0).map(move |col| core::Point3f::new(col as f32, row as f32, 0.0)))
+        .collect();
+    let object_points: core::Vector<core::Vector<core::Point3f>> = views.iter().map(|_| square.clone()).collect();
+    let image_points: core::Vector<core::Vector<core::Point2f>> = views.iter().cloned().collect();
+
+    let mut camera = Mat::default();
+    let mut distortion = Mat::default();
+    let mut rotations = core::Vector::<Mat>::new();
+    let mut translations = core::Vector::<Mat>::new();
+    let criteria = core::TermCriteria::new(core::TermCriteria_COUNT + core::TermCriteria_EPS, 30, f64::EPSILON).context("critério de parada")?;
+    let error = calib3d::calibrate_camera(
+        &object_points,
+        &image_points,
+        core::Size::new(image_size.0, image_size.1),
+        &mut camera,
+        &mut distortion,
+        &mut rotations,
+        &mut translations,
+        0,
+        criteria,
+    )
+    .context("calibração da câmera")?;
+
+    let (width, height) = (f64::from(image_size.0), f64::from(image_size.1));
+    let intrinsic = |row: i32, col: i32| -> Result<f64> { Ok(*camera.at_2d::<f64>(row, col).context("leitura da matriz da câmera")?) };
+    let coefficient = |index: i32| -> Result<f64> { Ok(*distortion.at::<f64>(index).context("leitura dos coeficientes")?) };
+    let profile = LensProfile {
+        fx: intrinsic(0, 0)? / width,
+        fy: intrinsic(1, 1)? / height,
+        cx: intrinsic(0, 2)? / width,
+        cy: intrinsic(1, 2)? / height,
+        k1: coefficient(0)?,
+        k2: coefficient(1)?,
+        p1: coefficient(2)?,
+        p2: coefficient(3)?,
+        k3: coefficient(4)?,
+    };
+    Ok((profile, error))
+}
+
+pub fn undistort(img: &Mat, profile: &LensProfile, alpha: f64) -> Result<Mat> { // Remove a distorção; alpha 0 = só pixels válidos, 1 = mantém a imagem inteira
+    let camera = profile.camera_matrix(img.cols(), img.rows())?;
+    let distortion = profile.distortion()?;
+    let size = core::Size::new(img.cols(), img.rows());
+    let target = calib3d::get_optimal_new_camera_matrix_def(&camera, &distortion, size, alpha.clamp(0.0, 1.0))
+        .context("matriz da câmera corrigida")?;
+
+    let mut result = Mat::default();
+    calib3d::undistort(img, &mut result, &camera, &distortion, &target).context("correção da distorção da lente")?;
+    Ok(result)
+}
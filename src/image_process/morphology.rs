@@ -0,0 +1,110 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*};
+
+use super::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MorphOperation { // Operação morfológica
+    Erode, // Encolhe as áreas claras
+    Dilate, // Expande as áreas claras
+    Open, // Erosão seguida de dilatação: remove pontos claros isolados
+    Close, // Dilatação seguida de erosão: fecha buracos escuros
+    Gradient, // Dilatação menos erosão: contorno dos objetos
+    TopHat, // Original menos abertura: detalhes claros menores que o elemento
+    BlackHat, // Fechamento menos original: detalhes escuros menores que o elemento
+}
+
+impl MorphOperation {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("erode", "Erosão"),
+        ("dilate", "Dilatação"),
+        ("open", "Abertura"),
+        ("close", "Fechamento"),
+        ("gradient", "Gradiente"),
+        ("tophat", "Top-hat"),
+        ("blackhat", "Black-hat"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => MorphOperation::Dilate,
+            2 => MorphOperation::Open,
+            3 => MorphOperation::Close,
+            4 => MorphOperation::Gradient,
+            5 => MorphOperation::TopHat,
+            6 => MorphOperation::BlackHat,
+            _ => MorphOperation::Erode,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn code(self) -> i32 {
+        match self {
+            MorphOperation::Erode => imgproc::MORPH_ERODE,
+            MorphOperation::Dilate => imgproc::MORPH_DILATE,
+            MorphOperation::Open => imgproc::MORPH_OPEN,
+            MorphOperation::Close => imgproc::MORPH_CLOSE,
+            MorphOperation::Gradient => imgproc::MORPH_GRADIENT,
+            MorphOperation::TopHat => imgproc::MORPH_TOPHAT,
+            MorphOperation::BlackHat => imgproc::MORPH_BLACKHAT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MorphShape { // Formato do elemento estruturante
+    Rect,
+    Ellipse,
+    Cross,
+}
+
+impl MorphShape {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("rect", "Retângulo"),
+        ("ellipse", "Elipse"),
+        ("cross", "Cruz"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => MorphShape::Ellipse,
+            2 => MorphShape::Cross,
+            _ => MorphShape::Rect,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn code(self) -> i32 {
+        match self {
+            MorphShape::Rect => imgproc::MORPH_RECT,
+            MorphShape::Ellipse => imgproc::MORPH_ELLIPSE,
+            MorphShape::Cross => imgproc::MORPH_CROSS,
+        }
+    }
+}
+
+pub fn morphology(img: &Mat, operation: MorphOperation, shape: MorphShape, size: i32, iterations: i32) -> Result<Mat> { // Aplica a operação com o elemento escolhido
+    let size = size.clamp(1, 51);
+    let element = imgproc::get_structuring_element(shape.code(), core::Size::new(size, size), core::Point::new(-1, -1))
+        .context("criação do elemento estruturante")?;
+
+    let mut result = Mat::default();
+    imgproc::morphology_ex(
+        img,
+        &mut result,
+        operation.code(),
+        &element,
+        core::Point::new(-1, -1),
+        iterations.clamp(1, 20),
+        core::BORDER_CONSTANT,
+        imgproc::morphology_default_border_value().context("borda da operação morfológica")?,
+    )
+    .context("operação morfológica")?;
+    Ok(result)
+}
@@ -0,0 +1,128 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::pixels::{split_alpha, to_8bit};
+use super::{ImageError, Result};
+
+pub type Corners = [(f64, f64); 4]; // Cantos relativos (0 a 1): superior esquerdo, superior direito, inferior direito, inferior esquerdo
+
+pub const FULL_FRAME: Corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+
+pub const CORNER_PARAMS: &[(usize, bool, &str, &str)] = &[ // (canto, é x?, chave, rótulo) de cada parâmetro da perspectiva
+    (0, true, "tl_x", "Superior esquerdo: x"),
+    (0, false, "tl_y", "Superior esquerdo: y"),
+    (1, true, "tr_x", "Superior direito: x"),
+    (1, false, "tr_y", "Superior direito: y"),
+    (2, true, "br_x", "Inferior direito: x"),
+    (2, false, "br_y", "Inferior direito: y"),
+    (3, true, "bl_x", "Inferior esquerdo: x"),
+    (3, false, "bl_y", "Inferior esquerdo: y"),
+];
+
+const DETECTION_SIZE: f64 = 1000.0; // A detecção roda numa cópia reduzida (lado maior), bem mais rápida
+
+fn extreme(points: &[(f64, f64)], key: impl Fn(&(f64, f64)) -> f64) -> (f64, f64) { // Ponto com o maior valor da chave
+    points.iter().copied().max_by(|a, b| key(a).total_cmp(&key(b))).unwrap_or((0.0, 0.0))
+}
+
+fn order_corners(points: &[(f64, f64)]) -> Corners { // Ordena os quatro pontos no sentido horário a partir do superior esquerdo
+    [
+        extreme(points, |(x, y)| -(x + y)), // Superior esquerdo: menor x + y
+        extreme(points, |(x, y)| x - y), // Superior direito: maior x - y
+        extreme(points, |(x, y)| x + y), // Inferior direito: maior x + y
+        extreme(points, |(x, y)| y - x), // Inferior esquerdo: maior y - x
+    ]
+}
+
+pub fn detect_document(img: &Mat) -> Result<Option<Corners>> { // Maior quadrilátero da imagem (folha, quadro branco, recibo)
+    let (color, _) = split_alpha(img)?;
+    let color = to_8bit(&color)?;
+    let scale = (DETECTION_SIZE / f64::from(color.cols().max(color.rows()))).min(1.0);
+    let mut small = Mat::default();
+    imgproc::resize(&color, &mut small, core::Size::new(0, 0), scale, scale, imgproc::INTER_AREA).context("redução para a detecção")?;
+
+    let mut gray = Mat::default();
+    imgproc::cvt_color(&small, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para escala de cinza")?;
+    let mut blurred = Mat::default();
+    imgproc::gaussian_blur(&gray, &mut blurred, core::Size::new(5, 5), 0.0, 0.0, core::BORDER_DEFAULT, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("suavização para a detecção")?;
+    let mut edges = Mat::default();
+    imgproc::canny(&blurred, &mut edges, 75.0, 200.0, 3, false).context("bordas para a detecção")?;
+    let mut closed = Mat::default(); // Fecha pequenas falhas no contorno do documento
+    let element = imgproc::get_structuring_element(imgproc::MORPH_RECT, core::Size::new(3, 3), core::Point::new(-1, -1))
+        .context("criação do elemento estruturante")?;
+    let border = imgproc::morphology_default_border_value().context("borda da dilatação")?;
+    imgproc::dilate(&edges, &mut closed, &element, core::Point::new(-1, -1), 1, core::BORDER_CONSTANT, border).context("dilatação das bordas")?;
+
+    let mut contours = core::Vector::<core::Vector<core::Point>>::new();
+    imgproc::find_contours(&closed, &mut contours, imgproc::RETR_EXTERNAL, imgproc::CHAIN_APPROX_SIMPLE, core::Point::new(0, 0))
+        .context("busca dos contornos")?;
+
+    let (cols, rows) = (f64::from(small.cols()), f64::from(small.rows()));
+    let mut best: Option<(f64, core::Vector<core::Point>)> = None;
+    for contour in contours.iter() {
+        let perimeter = imgproc::arc_length(&contour, true).context("perímetro do contorno")?;
+        let mut polygon = core::Vector::<core::Point>::new();
+        imgproc::approx_poly_dp(&contour, &mut polygon, 0.02 * perimeter, true).context("simplificação do contorno")?;
+        if polygon.len() != 4 || !imgproc::is_contour_convex(&polygon).context("convexidade do contorno")? {
+            continue;
+        }
+        let area = imgproc::contour_area(&polygon, false).context("área do contorno")?;
+        let largest = match &best {
+            Some((largest, _)) => area > *largest,
+            None => true,
+        };
+        if largest && area >= 0.1 * cols * rows { // Ignora quadriláteros pequenos (texto, detalhes)
+            best = Some((area, polygon));
+        }
+    }
+
+    Ok(best.map(|(_, polygon)| {
+        let points: Vec<(f64, f64)> = polygon.iter().map(|point| (f64::from(point.x) / cols, f64::from(point.y) / rows)).collect();
+        order_corners(&points)
+    }))
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+pub fn warp_document(img: &Mat, corners: &Corners) -> Result<Mat> { // Endireita o quadrilátero num retângulo plano
+    let (cols, rows) = (f64::from(img.cols()), f64::from(img.rows()));
+    let points = corners.map(|(x, y)| (x.clamp(0.0, 1.0) * cols, y.clamp(0.0, 1.0) * rows));
+    let [top_left, top_right, bottom_right, bottom_left] = points;
+
+    let area = points // Fórmula do laço: cantos alinhados ou repetidos não formam um documento
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f64>()
+        .abs()
+        / 2.0;
+    if area < 1.0 {
+        return Err(ImageError::InvalidParam("os cantos da perspectiva não formam um quadrilátero".to_string()));
+    }
+
+    let width = distance(top_left, top_right).max(distance(bottom_left, bottom_right)).round().max(1.0);
+    let height = distance(top_left, bottom_left).max(distance(top_right, bottom_right)).round().max(1.0);
+    let source: core::Vector<core::Point2f> = points.iter().map(|(x, y)| core::Point2f::new(*x as f32, *y as f32)).collect();
+    let target: core::Vector<core::Point2f> = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+        .iter()
+        .map(|(x, y)| core::Point2f::new(*x as f32, *y as f32))
+        .collect();
+    let matrix = imgproc::get_perspective_transform(&source, &target, core::DECOMP_LU).context("cálculo da perspectiva")?;
+
+    let mut result = Mat::default();
+    imgproc::warp_perspective(
+        img,
+        &mut result,
+        &matrix,
+        core::Size::new(width as i32, height as i32),
+        imgproc::INTER_LINEAR,
+        core::BORDER_REPLICATE,
+        core::Scalar::default(),
+    )
+    .context("correção de perspectiva")?;
+    Ok(result)
+}
@@ -0,0 +1,77 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*};
+
+use super::Result;
+
+pub fn split_alpha(img: &Mat) -> Result<(Mat, Option<Mat>)> { // Separa a transparência (BGRA) da cor; os filtros trabalham só na cor
+    if img.channels() != 4 {
+        return Ok((img.clone(), None));
+    }
+    let mut channels = core::Vector::<Mat>::new();
+    core::split(img, &mut channels).context("separação do canal alfa")?;
+    let alpha = channels.get(3).context("leitura do canal alfa")?;
+    channels.remove(3).context("remoção do canal alfa")?;
+
+    let mut color = Mat::default();
+    core::merge(&channels, &mut color).context("junção dos canais de cor")?;
+    Ok((color, Some(alpha)))
+}
+
+pub fn merge_alpha(color: &Mat, alpha: &Mat) -> Result<Mat> { // Devolve a transparência depois do filtro (a cor já está em 3 canais)
+    let mut alpha = alpha.clone();
+    if alpha.depth() != color.depth() { // Ex.: alfa de 16 bits com um filtro que só existe em 8 bits
+        let scale = if color.depth() == core::CV_16U { 257.0 } else { 1.0 / 257.0 };
+        let mut converted = Mat::default();
+        alpha.convert_to(&mut converted, color.depth(), scale, 0.0).context("conversão do canal alfa")?;
+        alpha = converted;
+    }
+
+    let mut channels = core::Vector::<Mat>::new();
+    core::split(color, &mut channels).context("separação dos canais de cor")?;
+    channels.push(alpha);
+    let mut result = Mat::default();
+    core::merge(&channels, &mut result).context("junção do canal alfa")?;
+    Ok(result)
+}
+
+pub fn to_8bit(img: &Mat) -> Result<Mat> { // Converte para 8 bits mantendo a escala (65535 vira 255)
+    if img.depth() == core::CV_8U {
+        return Ok(img.clone());
+    }
+    let scale = match img.depth() {
+        core::CV_16U => 1.0 / 257.0,
+        core::CV_32F | core::CV_64F => 255.0, // Imagens em ponto flutuante vão de 0 a 1
+        _ => 1.0,
+    };
+    let mut result = Mat::default();
+    img.convert_to(&mut result, core::CV_8U, scale, 0.0).context("conversão para 8 bits")?;
+    Ok(result)
+}
+
+pub fn to_16bit(img: &Mat) -> Result<Mat> { // Volta para 16 bits depois de um filtro que só existe em 8 bits
+    let mut result = Mat::default();
+    img.convert_to(&mut result, core::CV_16U, 257.0, 0.0).context("conversão para 16 bits")?;
+    Ok(result)
+}
+
+pub(crate) fn full_scale(img: &Mat) -> f64 { // Valor máximo de um canal na profundidade da imagem
+    if img.depth() == core::CV_16U { 65535.0 } else { 255.0 }
+}
+
+pub fn flatten_alpha(img: &Mat) -> Result<Mat> { // Compõe a imagem sobre fundo branco, para formatos sem transparência (8 bits)
+    let (color, alpha) = split_alpha(img)?;
+    let Some(alpha) = alpha else {
+        return Ok(color);
+    };
+
+    let mut opacity = Mat::default();
+    alpha.convert_to(&mut opacity, core::CV_32F, 1.0, 0.0).context("peso da cor")?;
+    let mut transparency = Mat::default();
+    alpha.convert_to(&mut transparency, core::CV_32F, -1.0, 255.0).context("peso do fundo")?;
+    let white = Mat::new_rows_cols_with_default(color.rows(), color.cols(), color.typ(), core::Scalar::all(255.0))
+        .context("criação do fundo branco")?;
+
+    let mut result = Mat::default();
+    imgproc::blend_linear(&color, &white, &opacity, &transparency, &mut result).context("composição sobre o fundo branco")?;
+    Ok(result)
+}
@@ -0,0 +1,99 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::pixels::full_scale;
+use super::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SharpenMode { // Como os detalhes realçados voltam para a imagem
+    Unsharp, // Máscara de nitidez clássica: soma a diferença para a versão desfocada
+    HighPass, // Camada passa-alta combinada em modo "sobreposição"
+}
+
+impl SharpenMode {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("unsharp", "Máscara de nitidez"),
+        ("high_pass", "Passa-alta (sobreposição)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => SharpenMode::HighPass,
+            _ => SharpenMode::Unsharp,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+fn invert(img: &Mat) -> Result<Mat> { // 1 - x, com a imagem de 0 a 1
+    let mut result = Mat::default();
+    img.convert_to(&mut result, -1, -1.0, 1.0).context("inversão")?;
+    Ok(result)
+}
+
+fn overlay(base: &Mat, layer: &Mat) -> Result<Mat> { // Modo de mesclagem "sobreposição" (imagens de 0 a 1)
+    let mut dark = Mat::default(); // 2ab, usado onde a base é escura
+    core::multiply(base, layer, &mut dark, 2.0, -1).context("sobreposição (tons escuros)")?;
+
+    let mut light = Mat::default(); // 1 - 2(1-a)(1-b), usado onde a base é clara
+    core::multiply(&invert(base)?, &invert(layer)?, &mut light, 2.0, -1).context("sobreposição (tons claros)")?;
+    let light = invert(&light)?;
+
+    let mut is_light = Mat::default();
+    imgproc::threshold(base, &mut is_light, 0.5, 1.0, imgproc::THRESH_BINARY).context("máscara da sobreposição")?;
+    let mut difference = Mat::default();
+    core::subtract(&light, &dark, &mut difference, &Mat::default(), -1).context("sobreposição")?;
+    let mut weighted = Mat::default();
+    core::multiply(&is_light, &difference, &mut weighted, 1.0, -1).context("sobreposição")?;
+    let mut result = Mat::default();
+    core::add(&dark, &weighted, &mut result, &Mat::default(), -1).context("sobreposição")?;
+    Ok(result)
+}
+
+pub fn unsharp_mask(img: &Mat, mode: SharpenMode, radius: f64, amount: f64, threshold: f64, relative: bool) -> Result<Mat> { // Nitidez com raio, intensidade e limiar (em 8 ou 16 bits)
+    let sigma = if relative { // Raio dado por 1000 px do lado maior: o resultado fica igual em qualquer resolução
+        radius * f64::from(img.cols().max(img.rows())) / 1000.0
+    } else {
+        radius
+    }
+    .max(0.1);
+
+    let scale = full_scale(img);
+    let mut image = Mat::default();
+    img.convert_to(&mut image, core::CV_32F, 1.0 / scale, 0.0).context("conversão para ponto flutuante")?;
+    let mut blurred = Mat::default();
+    imgproc::gaussian_blur(&image, &mut blurred, core::Size::default(), sigma, sigma, core::BORDER_DEFAULT, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("desfoque da máscara de nitidez")?;
+
+    let mut detail = Mat::default(); // Detalhes finos: original menos desfocada
+    core::subtract(&image, &blurred, &mut detail, &Mat::default(), -1).context("cálculo dos detalhes")?;
+    if threshold > 0.0 { // Diferenças pequenas (ruído, pele) não são realçadas
+        let mut magnitude = Mat::default();
+        core::absdiff(&image, &blurred, &mut magnitude).context("limiar da máscara de nitidez")?;
+        let mut keep = Mat::default();
+        imgproc::threshold(&magnitude, &mut keep, threshold / 255.0, 1.0, imgproc::THRESH_BINARY).context("limiar da máscara de nitidez")?;
+        let mut kept = Mat::default();
+        core::multiply(&detail, &keep, &mut kept, 1.0, -1).context("limiar da máscara de nitidez")?;
+        detail = kept;
+    }
+
+    let sharpened = match mode {
+        SharpenMode::Unsharp => {
+            let mut sharpened = Mat::default();
+            core::scale_add(&detail, amount, &image, &mut sharpened).context("máscara de nitidez")?;
+            sharpened
+        }
+        SharpenMode::HighPass => {
+            let mut layer = Mat::default(); // Camada cinza (0.5) com os detalhes
+            detail.convert_to(&mut layer, -1, amount, 0.5).context("camada passa-alta")?;
+            overlay(&image, &layer)?
+        }
+    };
+
+    let mut result = Mat::default();
+    sharpened.convert_to(&mut result, img.depth(), scale, 0.0).context("conversão de volta para inteiros")?;
+    Ok(result)
+}
@@ -0,0 +1,136 @@
+use anyhow::Context;
+use opencv::{imgproc, prelude::*, core::AlgorithmHint};
+
+use super::{color, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdMethod { // Como o limiar é escolhido
+    Fixed, // Valor do slider
+    Otsu, // Automático: separa melhor os dois grupos de tons
+    Triangle, // Automático: bom para histogramas com um pico dominante
+    AdaptiveMean, // Limiar local: média da vizinhança menos C
+    AdaptiveGaussian, // Limiar local: média ponderada (gaussiana) menos C
+}
+
+impl ThresholdMethod {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("fixed", "Fixo"),
+        ("otsu", "Otsu (automático)"),
+        ("triangle", "Triângulo (automático)"),
+        ("adaptive_mean", "Adaptativo (média)"),
+        ("adaptive_gaussian", "Adaptativo (gaussiano)"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ThresholdMethod::Otsu,
+            2 => ThresholdMethod::Triangle,
+            3 => ThresholdMethod::AdaptiveMean,
+            4 => ThresholdMethod::AdaptiveGaussian,
+            _ => ThresholdMethod::Fixed,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdMode { // O que acontece com os pixels acima e abaixo do limiar
+    Binary, // Acima = máximo, abaixo = 0
+    BinaryInverse, // Acima = 0, abaixo = máximo
+    Truncate, // Acima = limiar, abaixo fica igual
+    ToZero, // Acima fica igual, abaixo = 0
+    ToZeroInverse, // Acima = 0, abaixo fica igual
+}
+
+impl ThresholdMode {
+    pub const OPTIONS: &'static [(&'static str, &'static str)] = &[
+        ("binary", "Binário"),
+        ("binary_inv", "Binário invertido"),
+        ("truncate", "Truncar"),
+        ("to_zero", "Zerar abaixo"),
+        ("to_zero_inv", "Zerar acima"),
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            1 => ThresholdMode::BinaryInverse,
+            2 => ThresholdMode::Truncate,
+            3 => ThresholdMode::ToZero,
+            4 => ThresholdMode::ToZeroInverse,
+            _ => ThresholdMode::Binary,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    fn code(self) -> i32 {
+        match self {
+            ThresholdMode::Binary => imgproc::THRESH_BINARY,
+            ThresholdMode::BinaryInverse => imgproc::THRESH_BINARY_INV,
+            ThresholdMode::Truncate => imgproc::THRESH_TRUNC,
+            ThresholdMode::ToZero => imgproc::THRESH_TOZERO,
+            ThresholdMode::ToZeroInverse => imgproc::THRESH_TOZERO_INV,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Threshold { // Configuração da binarização
+    pub method: ThresholdMethod,
+    pub mode: ThresholdMode,
+    pub value: f64, // Limiar fixo (0 a 255)
+    pub max_value: f64, // Valor dos pixels "ligados"
+    pub block_size: i32, // Vizinhança do limiar adaptativo (ímpar)
+    pub c: f64, // Subtraído da média local no modo adaptativo
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Threshold {
+            method: ThresholdMethod::Otsu,
+            mode: ThresholdMode::Binary,
+            value: 128.0,
+            max_value: 255.0,
+            block_size: 15,
+            c: 10.0,
+        }
+    }
+}
+
+pub fn threshold(img: &Mat, settings: &Threshold) -> Result<Mat> { // Binariza a imagem em tons de cinza e volta para BGR
+    let mut gray = Mat::default();
+    imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para escala de cinza")?;
+    let max_value = settings.max_value.clamp(0.0, 255.0);
+
+    let mut result = Mat::default();
+    match settings.method {
+        ThresholdMethod::Fixed => {
+            imgproc::threshold(&gray, &mut result, settings.value, max_value, settings.mode.code()).context("limiar fixo")?;
+        }
+        ThresholdMethod::Otsu | ThresholdMethod::Triangle => {
+            let automatic = if settings.method == ThresholdMethod::Otsu { imgproc::THRESH_OTSU } else { imgproc::THRESH_TRIANGLE };
+            imgproc::threshold(&gray, &mut result, 0.0, max_value, settings.mode.code() | automatic).context("limiar automático")?;
+        }
+        ThresholdMethod::AdaptiveMean | ThresholdMethod::AdaptiveGaussian => {
+            let adaptive = if settings.method == ThresholdMethod::AdaptiveMean {
+                imgproc::ADAPTIVE_THRESH_MEAN_C
+            } else {
+                imgproc::ADAPTIVE_THRESH_GAUSSIAN_C
+            };
+            let mode = match settings.mode { // O limiar adaptativo só tem os modos binário e binário invertido
+                ThresholdMode::BinaryInverse | ThresholdMode::ToZeroInverse => imgproc::THRESH_BINARY_INV,
+                _ => imgproc::THRESH_BINARY,
+            };
+            let block_size = settings.block_size.max(3) | 1; // O bloco precisa ser ímpar e maior que 1
+            imgproc::adaptive_threshold(&gray, &mut result, max_value, adaptive, mode, block_size, settings.c)
+                .context("limiar adaptativo")?;
+        }
+    }
+    color::to_bgr(result)
+}
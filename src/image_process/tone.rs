@@ -0,0 +1,324 @@
+use anyhow::Context;
+use opencv::{core, imgproc, prelude::*, core::AlgorithmHint};
+
+use super::pixels::{split_alpha, to_8bit};
+use super::{ImageError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Levels { // Ponto preto, ponto branco e gama de um canal
+    pub black: f64, // Valores até aqui viram 0
+    pub white: f64, // Valores a partir daqui viram 255
+    pub gamma: f64, // Tons médios (> 1 clareia, < 1 escurece)
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels { black: 0.0, white: 255.0, gamma: 1.0 }
+    }
+}
+
+impl Levels {
+    fn map(&self, value: f64) -> f64 { // Aplica os níveis a um valor de 0 a 255
+        let white = self.white.max(self.black + 1.0);
+        let normalized = ((value - self.black) / (white - self.black)).clamp(0.0, 1.0);
+        normalized.powf(1.0 / self.gamma.max(0.01)) * 255.0
+    }
+}
+
+pub fn channel_lut(transform: impl Fn(f64) -> f64) -> [u8; 256] { // Tabela de 256 entradas a partir de uma função de tons
+    let mut table = [0u8; 256];
+    for (value, entry) in table.iter_mut().enumerate() {
+        *entry = transform(value as f64).round().clamp(0.0, 255.0) as u8;
+    }
+    table
+}
+
+pub fn apply_lut(img: &Mat, tables: [[u8; 256]; 3]) -> Result<Mat> { // Aplica uma tabela por canal (na ordem dos canais da imagem) com core::lut; imagens cinza usam a segunda tabela
+    let lut = if img.channels() == 1 {
+        let mut lut = Mat::new_rows_cols_with_default(1, 256, core::CV_8UC1, core::Scalar::all(0.0)).context("criação da tabela de tons")?;
+        for (index, value) in tables[1].iter().enumerate() {
+            *lut.at_mut::<u8>(index as i32).context("preenchimento da tabela de tons")? = *value;
+        }
+        lut
+    } else {
+        let mut lut = Mat::new_rows_cols_with_default(1, 256, core::CV_8UC3, core::Scalar::all(0.0)).context("criação da tabela de tons")?;
+        for index in 0..256 {
+            *lut.at_mut::<core::Vec3b>(index as i32).context("preenchimento da tabela de tons")? =
+                core::Vec3b::from([tables[0][index], tables[1][index], tables[2][index]]);
+        }
+        lut
+    };
+
+    let mut result = Mat::default();
+    core::lut(img, &lut, &mut result).context("aplicação da tabela de tons")?;
+    Ok(result)
+}
+
+fn apply_tone(img: &Mat, shared: bool, transform: impl Fn(usize, f64) -> f64) -> Result<Mat> { // Aplica uma função de tons (canal, valor de 0 a 255) em 8 ou 16 bits; `shared` indica que todos os canais usam a mesma função
+    let count = img.channels().max(1) as usize;
+    let source = |channel: usize| if shared { 0 } else { channel.min(count - 1) }; // Canal cuja tabela é usada
+    match img.depth() {
+        core::CV_8U => {
+            let tables = [0, 1, 2].map(|channel| channel_lut(|value| transform(source(channel), value)));
+            apply_lut(img, tables)
+        }
+        core::CV_16U => { // O core::lut só aceita 8 bits: usa tabelas de 65536 entradas, montadas uma vez e repetidas nos canais iguais
+            let unique = if shared { 1 } else { count };
+            let tables: Vec<Vec<u16>> = (0..unique)
+                .map(|channel| {
+                    (0..=u16::MAX)
+                        .map(|value| (transform(channel, f64::from(value) / 257.0) * 257.0).round().clamp(0.0, 65535.0) as u16)
+                        .collect()
+                })
+                .collect();
+            let tables: Vec<&[u16]> = (0..count).map(|channel| tables[source(channel)].as_slice()).collect();
+            let mut result = img.try_clone().context("cópia da imagem")?;
+            let bytes = result.data_bytes_mut().context("acesso aos pixels")?;
+            for (index, sample) in bytes.chunks_exact_mut(2).enumerate() {
+                let value = u16::from_ne_bytes([sample[0], sample[1]]);
+                sample.copy_from_slice(&tables[index % count][usize::from(value)].to_ne_bytes());
+            }
+            Ok(result)
+        }
+        depth => Err(ImageError::InvalidParam(format!("profundidade de cor não suportada: {}", depth))),
+    }
+}
+
+pub fn levels(img: &Mat, master: &Levels, channels: [&Levels; 3]) -> Result<Mat> { // Níveis gerais seguidos dos níveis de cada canal (B, G, R)
+    let gray = img.channels() == 1; // Imagem cinza: só os níveis gerais fazem sentido
+    let shared = gray || (channels[0] == channels[1] && channels[1] == channels[2]); // Só os níveis gerais mexidos: uma tabela basta
+    apply_tone(img, shared, |channel, value| {
+        let value = master.map(value);
+        if gray { value } else { channels[channel].map(value) }
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Histogram { // Contagem de pixels por valor (256 posições) de cada canal
+    pub red: Vec<f32>,
+    pub green: Vec<f32>,
+    pub blue: Vec<f32>,
+    pub luminance: Vec<f32>,
+}
+
+fn channel_histogram(img: &Mat, channel: i32) -> Result<Vec<f32>> {
+    let mut images = core::Vector::<Mat>::new();
+    images.push(img.clone());
+    let mut hist = Mat::default();
+    imgproc::calc_hist(
+        &images,
+        &core::Vector::from_slice(&[channel]),
+        &Mat::default(),
+        &mut hist,
+        &core::Vector::from_slice(&[256]),
+        &core::Vector::from_slice(&[0.0, 256.0]),
+        false,
+    )
+    .context("cálculo do histograma")?;
+    Ok(hist.data_typed::<f32>().context("leitura do histograma")?.to_vec())
+}
+
+pub fn histogram(img: &Mat) -> Result<Histogram> { // Histograma RGB e de luminância (imagens cinza repetem o mesmo canal)
+    let (color, _) = split_alpha(img)?; // A transparência não entra no histograma
+    let img = &to_8bit(&color)?; // 16 bits são agrupados nas mesmas 256 posições
+    if img.channels() == 1 {
+        let gray = channel_histogram(img, 0)?;
+        return Ok(Histogram { red: gray.clone(), green: gray.clone(), blue: gray.clone(), luminance: gray });
+    }
+
+    let mut gray = Mat::default();
+    imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para escala de cinza")?;
+    Ok(Histogram {
+        blue: channel_histogram(img, 0)?,
+        green: channel_histogram(img, 1)?,
+        red: channel_histogram(img, 2)?,
+        luminance: channel_histogram(&gray, 0)?,
+    })
+}
+
+pub const CURVE_POINTS: usize = 5; // Pontos de controle por curva (as pontas ficam presas em x = 0 e x = 255)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curve { // Curva de tons definida por pontos de controle (entrada, saída), de 0 a 255
+    pub points: [(f64, f64); CURVE_POINTS],
+}
+
+impl Default for Curve {
+    fn default() -> Self { // Curva identidade (não altera a imagem)
+        Curve { points: [(0.0, 0.0), (64.0, 64.0), (128.0, 128.0), (192.0, 192.0), (255.0, 255.0)] }
+    }
+}
+
+impl Curve {
+    pub fn set_x(&mut self, index: usize, x: f64) { // Move um ponto interno na horizontal (as pontas ficam fixas)
+        if index > 0 && index < CURVE_POINTS - 1 {
+            self.points[index].0 = x.clamp(1.0, 254.0);
+        }
+    }
+
+    pub fn set_y(&mut self, index: usize, y: f64) {
+        if let Some(point) = self.points.get_mut(index) {
+            point.1 = y.clamp(0.0, 255.0);
+        }
+    }
+
+    pub fn prepare(&self) -> PreparedCurve { // Ordena os pontos e calcula as tangentes uma única vez (Fritsch-Carlson)
+        let mut points = self.points; // Os pontos podem ter sido arrastados para além dos vizinhos
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut slopes = [0.0; CURVE_POINTS - 1];
+        for (slope, pair) in slopes.iter_mut().zip(points.windows(2)) {
+            *slope = (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0).max(1e-6);
+        }
+
+        let mut tangents = [0.0; CURVE_POINTS];
+        tangents[0] = slopes[0];
+        tangents[CURVE_POINTS - 1] = slopes[CURVE_POINTS - 2];
+        for index in 1..CURVE_POINTS - 1 {
+            let (before, after) = (slopes[index - 1], slopes[index]);
+            tangents[index] = if before * after <= 0.0 { 0.0 } else { 2.0 / (1.0 / before + 1.0 / after) };
+        }
+        PreparedCurve { points, tangents }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreparedCurve { // Curva pronta para ser avaliada muitas vezes (tabelas de 256 ou 65536 entradas, desenho na interface)
+    points: [(f64, f64); CURVE_POINTS], // Pontos ordenados pela entrada
+    tangents: [f64; CURVE_POINTS],
+}
+
+impl PreparedCurve {
+    pub fn map(&self, value: f64) -> f64 { // Interpolação cúbica monotônica: a curva não passa dos pontos
+        let points = &self.points;
+        let segment = points.windows(2).position(|pair| value <= pair[1].0).unwrap_or(CURVE_POINTS - 2);
+        let ((x0, y0), (x1, y1)) = (points[segment], points[segment + 1]);
+        let width = (x1 - x0).max(1e-6);
+        let t = ((value - x0) / width).clamp(0.0, 1.0);
+        let (t2, t3) = (t * t, t * t * t);
+        let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+            + (t3 - 2.0 * t2 + t) * width * self.tangents[segment]
+            + (-2.0 * t3 + 3.0 * t2) * y1
+            + (t3 - t2) * width * self.tangents[segment + 1];
+        y.clamp(0.0, 255.0)
+    }
+}
+
+pub const CURVE_CHANNELS: [&str; 4] = ["Geral", "Vermelho", "Verde", "Azul"]; // Curvas editáveis, na ordem do filtro
+
+pub const CURVE_PARAMS: &[(usize, usize, bool, &str, &str)] = &[ // (curva, ponto, é entrada?, chave, rótulo) de cada parâmetro das curvas
+    (0, 0, false, "master_y0", "Geral: saída do ponto 1"),
+    (0, 1, true, "master_x1", "Geral: entrada do ponto 2"),
+    (0, 1, false, "master_y1", "Geral: saída do ponto 2"),
+    (0, 2, true, "master_x2", "Geral: entrada do ponto 3"),
+    (0, 2, false, "master_y2", "Geral: saída do ponto 3"),
+    (0, 3, true, "master_x3", "Geral: entrada do ponto 4"),
+    (0, 3, false, "master_y3", "Geral: saída do ponto 4"),
+    (0, 4, false, "master_y4", "Geral: saída do ponto 5"),
+    (1, 0, false, "red_y0", "Vermelho: saída do ponto 1"),
+    (1, 1, true, "red_x1", "Vermelho: entrada do ponto 2"),
+    (1, 1, false, "red_y1", "Vermelho: saída do ponto 2"),
+    (1, 2, true, "red_x2", "Vermelho: entrada do ponto 3"),
+    (1, 2, false, "red_y2", "Vermelho: saída do ponto 3"),
+    (1, 3, true, "red_x3", "Vermelho: entrada do ponto 4"),
+    (1, 3, false, "red_y3", "Vermelho: saída do ponto 4"),
+    (1, 4, false, "red_y4", "Vermelho: saída do ponto 5"),
+    (2, 0, false, "green_y0", "Verde: saída do ponto 1"),
+    (2, 1, true, "green_x1", "Verde: entrada do ponto 2"),
+    (2, 1, false, "green_y1", "Verde: saída do ponto 2"),
+    (2, 2, true, "green_x2", "Verde: entrada do ponto 3"),
+    (2, 2, false, "green_y2", "Verde: saída do ponto 3"),
+    (2, 3, true, "green_x3", "Verde: entrada do ponto 4"),
+    (2, 3, false, "green_y3", "Verde: saída do ponto 4"),
+    (2, 4, false, "green_y4", "Verde: saída do ponto 5"),
+    (3, 0, false, "blue_y0", "Azul: saída do ponto 1"),
+    (3, 1, true, "blue_x1", "Azul: entrada do ponto 2"),
+    (3, 1, false, "blue_y1", "Azul: saída do ponto 2"),
+    (3, 2, true, "blue_x2", "Azul: entrada do ponto 3"),
+    (3, 2, false, "blue_y2", "Azul: saída do ponto 3"),
+    (3, 3, true, "blue_x3", "Azul: entrada do ponto 4"),
+    (3, 3, false, "blue_y3", "Azul: saída do ponto 4"),
+    (3, 4, false, "blue_y4", "Azul: saída do ponto 5"),
+];
+
+pub fn curves(img: &Mat, master: &Curve, channels: [&Curve; 3]) -> Result<Mat> { // Curva geral seguida das curvas de cada canal (B, G, R)
+    let gray = img.channels() == 1;
+    let shared = gray || (channels[0] == channels[1] && channels[1] == channels[2]); // Só a curva geral mexida: uma tabela basta
+    let master = master.prepare();
+    let channels = channels.map(Curve::prepare);
+    apply_tone(img, shared, |channel, value| {
+        let value = master.map(value);
+        if gray { value } else { channels[channel].map(value) }
+    })
+}
+
+fn on_luminance(img: &Mat, mut operation: impl FnMut(&Mat, &mut Mat) -> Result<()>) -> Result<Mat> { // Aplica a operação só no canal L do Lab (as cores são mantidas)
+    let mut result = Mat::default();
+    if img.channels() == 1 {
+        operation(img, &mut result)?;
+        return Ok(result);
+    }
+
+    let mut lab = Mat::default();
+    imgproc::cvt_color(img, &mut lab, imgproc::COLOR_BGR2Lab, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão para Lab")?;
+    let mut channels = core::Vector::<Mat>::new();
+    core::split(&lab, &mut channels).context("separação dos canais Lab")?;
+
+    let mut lightness = Mat::default();
+    operation(&channels.get(0).context("canal L")?, &mut lightness)?;
+    channels.set(0, lightness).context("troca do canal L")?;
+
+    core::merge(&channels, &mut lab).context("junção dos canais Lab")?;
+    imgproc::cvt_color(&lab, &mut result, imgproc::COLOR_Lab2BGR, 0, AlgorithmHint::ALGO_HINT_DEFAULT)
+        .context("conversão de Lab para BGR")?;
+    Ok(result)
+}
+
+pub fn equalize(img: &Mat) -> Result<Mat> { // Equalização global do histograma da luminância
+    on_luminance(img, |channel, result| {
+        imgproc::equalize_hist(channel, result).context("equalização do histograma")?;
+        Ok(())
+    })
+}
+
+pub fn clahe(img: &Mat, clip_limit: f64, tile_size: i32) -> Result<Mat> { // Equalização adaptativa (CLAHE): realça o contraste local sem estourar o ruído
+    let mut clahe = imgproc::create_clahe(clip_limit, core::Size::new(tile_size, tile_size)).context("criação do CLAHE")?;
+    on_luminance(img, |channel, result| {
+        clahe.apply(channel, result).context("equalização adaptativa (CLAHE)")?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_curve_is_identity() {
+        let curve = Curve::default().prepare();
+        for value in [0.0, 1.0, 63.5, 128.0, 200.0, 255.0] {
+            assert!((curve.map(value) - value).abs() < 1e-9, "{}", value);
+        }
+    }
+
+    #[test]
+    fn curve_passes_through_its_points_without_overshoot() {
+        let curve = Curve { points: [(0.0, 0.0), (64.0, 100.0), (128.0, 110.0), (192.0, 250.0), (255.0, 255.0)] };
+        let prepared = curve.prepare();
+        for (x, y) in curve.points {
+            assert!((prepared.map(x) - y).abs() < 1e-9);
+        }
+        let samples: Vec<f64> = (0..=255).map(|value| prepared.map(f64::from(value))).collect();
+        assert!(samples.windows(2).all(|pair| pair[1] >= pair[0] - 1e-9)); // Pontos crescentes geram uma curva crescente
+    }
+
+    #[test]
+    fn dragged_points_are_sorted_once() {
+        let mut curve = Curve::default();
+        curve.set_x(1, 200.0); // O ponto 2 passa para depois do ponto 4
+        let mut sorted = curve;
+        sorted.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert_eq!(curve.prepare(), sorted.prepare());
+    }
+}
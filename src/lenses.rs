@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use opencv::core;
+use opencv::prelude::*;
+
+use crate::batch::{self, BatchError};
+use crate::image_process::{self, LensProfile};
+use crate::metadata;
+use crate::storage::{self, Document, Stored};
+
+pub const DEFAULT_BOARD: (i32, i32) = (9, 6); // Cantos internos do tabuleiro de xadrez (colunas, linhas)
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lens { // Perfil de lente salvo com nome, no mesmo formato dos presets (ver storage.rs)
+    pub name: String,
+    pub profile: LensProfile,
+    pub error: f64, // Erro médio de reprojeção da calibração, em pixels
+    pub images: usize, // Fotos usadas na calibração
+}
+
+impl Lens {
+    fn coefficients(&self) -> [(&'static str, f64); 9] {
+        let profile = &self.profile;
+        [
+            ("fx", profile.fx),
+            ("fy", profile.fy),
+            ("cx", profile.cx),
+            ("cy", profile.cy),
+            ("k1", profile.k1),
+            ("k2", profile.k2),
+            ("p1", profile.p1),
+            ("p2", profile.p2),
+            ("k3", profile.k3),
+        ]
+    }
+}
+
+impl Stored for Lens {
+    const KIND: &'static str = "perfil de lente";
+    const FOLDER: &'static str = "lenses"; // Ao lado dos presets
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn to_toml(&self) -> String {
+        let mut text = format!("name = {}\nerror = {}\nimages = {}\n", storage::quote(&self.name), self.error, self.images);
+        for (key, value) in self.coefficients() {
+            text.push_str(&format!("{} = {}\n", key, value));
+        }
+        text
+    }
+
+    fn from_toml(text: &str) -> anyhow::Result<Self> {
+        let document = Document::parse(text)?;
+        let lens = Lens {
+            name: document.text("name")?.to_string(),
+            profile: LensProfile {
+                fx: document.number("fx")?,
+                fy: document.number("fy")?,
+                cx: document.number("cx")?,
+                cy: document.number("cy")?,
+                k1: document.number("k1")?,
+                k2: document.number("k2")?,
+                p1: document.number("p1")?,
+                p2: document.number("p2")?,
+                k3: document.number("k3")?,
+            },
+            error: document.number("error").unwrap_or(0.0), // Campos informativos, opcionais em perfis escritos à mão
+            images: document.number("images").unwrap_or(0.0) as usize,
+        };
+        for (key, value) in lens.coefficients() { // Valores inválidos só quebrariam a correção mais tarde, no OpenCV
+            if !value.is_finite() {
+                bail!("o campo {} precisa ser um número finito", key);
+            }
+            if matches!(key, "fx" | "fy") && value <= 0.0 {
+                bail!("o campo {} precisa ser maior que zero", key);
+            }
+        }
+        Ok(lens)
+    }
+}
+
+pub fn lenses_dir() -> Option<PathBuf> {
+    storage::dir::<Lens>()
+}
+
+pub fn list() -> Vec<Lens> { // Perfis salvos, em ordem alfabética (arquivos inválidos são ignorados)
+    storage::list()
+}
+
+pub fn save(lens: &Lens) -> anyhow::Result<PathBuf> { // Salva (ou substitui) o perfil na pasta de configuração
+    storage::save(lens)
+}
+
+pub fn delete(lens: &Lens) -> anyhow::Result<()> {
+    storage::delete(lens)
+}
+
+pub fn find(name_or_path: &str) -> anyhow::Result<Lens> { // Aceita o caminho de um arquivo ou o nome de um perfil salvo
+    storage::find(name_or_path)
+}
+
+pub fn parse_board(text: &str) -> anyhow::Result<(i32, i32)> { // "9x6" = 9 cantos internos por linha, 6 por coluna
+    let (cols, rows) = text
+        .split_once(['x', 'X'])
+        .with_context(|| format!("tabuleiro inválido: {} (use colunas x linhas, ex.: 9x6)", text))?;
+    let cols = cols.trim().parse::<i32>().with_context(|| format!("tabuleiro inválido: {}", text))?;
+    let rows = rows.trim().parse::<i32>().with_context(|| format!("tabuleiro inválido: {}", text))?;
+    if cols < 2 || rows < 2 {
+        bail!("o tabuleiro precisa de pelo menos 2x2 cantos internos");
+    }
+    Ok((cols, rows))
+}
+
+#[derive(Debug, Clone)]
+struct BoardView { // Tabuleiro encontrado em uma foto
+    path: PathBuf,
+    size: (i32, i32),
+    corners: core::Vector<core::Point2f>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Calibration { // Resultado da calibração de uma pasta
+    pub lens: Lens,
+    pub rejected: Vec<BatchError>, // Fotos sem o tabuleiro ou com tamanho diferente
+}
+
+fn find_board(file: &Path, board: (i32, i32)) -> anyhow::Result<BoardView> { // Procura o tabuleiro em uma foto (com a orientação do EXIF aplicada)
+    let (img, _) = metadata::load_oriented(&file.display().to_string())?;
+    let corners = image_process::find_chessboard(&img, board)?
+        .with_context(|| format!("tabuleiro {}x{} não encontrado", board.0, board.1))?;
+    Ok(BoardView { path: file.to_path_buf(), size: (img.cols(), img.rows()), corners })
+}
+
+fn calibrate(name: &str, board: (i32, i32), views: Vec<BoardView>, mut rejected: Vec<BatchError>) -> anyhow::Result<Calibration> { // Calcula o perfil com as fotos do mesmo tamanho da primeira
+    let size = views.first().map(|view| view.size).context("nenhuma foto com o tabuleiro encontrada")?;
+    let mut corners = Vec::new();
+    for view in views {
+        if view.size == size {
+            corners.push(view.corners);
+        } else {
+            rejected.push(BatchError {
+                path: view.path,
+                message: format!("tamanho {}x{} diferente da primeira foto ({}x{})", view.size.0, view.size.1, size.0, size.1),
+            });
+        }
+    }
+
+    let (profile, error) = image_process::calibrate(&corners, size, board)?;
+    let lens = Lens { name: name.trim().to_string(), profile, error, images: corners.len() };
+    Ok(Calibration { lens, rejected })
+}
+
+pub fn calibrate_folder(dir: &Path, name: &str, board: (i32, i32), mut progress: impl FnMut(usize, usize, &Path)) -> anyhow::Result<Calibration> { // Procura o tabuleiro em todas as fotos e calibra
+    let (files, mut rejected) = batch::discover(dir, None);
+    let mut views = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        progress(index, files.len(), file);
+        match find_board(file, board) {
+            Ok(view) => views.push(view),
+            Err(e) => rejected.push(BatchError { path: file.clone(), message: format!("{:#}", e) }),
+        }
+    }
+    progress(files.len(), files.len(), dir);
+    calibrate(name, board, views, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lens_round_trips() {
+        let lens = Lens {
+            name: "Celular \"grande-angular\" [traseira]".to_string(),
+            profile: LensProfile { fx: 0.8123, fy: 1.0831, cx: 0.4987, cy: 0.5012, k1: -0.2875, k2: 0.0914, p1: 0.00012, p2: -0.000031, k3: -1.2e-7 },
+            error: 0.3417,
+            images: 14,
+        };
+        assert_eq!(Lens::from_toml(&lens.to_toml()).unwrap(), lens);
+    }
+
+    #[test]
+    fn reads_hand_written_profiles() {
+        let text = "# Perfil copiado de outra ferramenta\nname = 'GoPro'\nfx = 0.5  # em relação à largura\nfy = 0.66\ncx = 0.5\ncy = 0.5\nk1 = -0.3\nk2 = 0.1\np1 = 0\np2 = 0\nk3 = 0\n";
+        let lens = Lens::from_toml(text).unwrap();
+        assert_eq!(lens.name, "GoPro");
+        assert_eq!(lens.profile.k1, -0.3);
+        assert_eq!((lens.error, lens.images), (0.0, 0)); // Campos opcionais
+    }
+
+    #[test]
+    fn rejects_incomplete_profiles() {
+        assert!(Lens::from_toml("name = \"Sem coeficientes\"\n").is_err());
+        assert!(Lens::from_toml("name = \"x\"\nfx = \"1\"\nfy = 1\ncx = 0.5\ncy = 0.5\nk1 = 0\nk2 = 0\np1 = 0\np2 = 0\nk3 = 0\n").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_coefficients() {
+        let profile = |fx: &str, k1: &str| format!("name = \"x\"\nfx = {}\nfy = 1\ncx = 0.5\ncy = 0.5\nk1 = {}\nk2 = 0\np1 = 0\np2 = 0\nk3 = 0\n", fx, k1);
+        assert!(Lens::from_toml(&profile("1", "0")).is_ok());
+        for (fx, k1, field) in [("0", "0", "fx"), ("-0.5", "0", "fx"), ("inf", "0", "fx"), ("1", "nan", "k1"), ("1", "-inf", "k1")] {
+            let error = Lens::from_toml(&profile(fx, k1)).unwrap_err().to_string();
+            assert!(error.contains(field), "{}", error); // A mensagem diz qual campo está errado
+        }
+    }
+
+    #[test]
+    fn parses_board_sizes() {
+        assert_eq!(parse_board("9x6").unwrap(), (9, 6));
+        assert_eq!(parse_board(" 7 X 5 ").unwrap(), (7, 5));
+        assert!(parse_board("9").is_err());
+        assert!(parse_board("1x6").is_err());
+    }
+}
@@ -1,7 +1,25 @@
 mod ui;
+mod batch;
+mod cli;
 mod image_process;
+mod export;
+mod history;
+mod lenses;
+mod metadata;
+mod presets;
+mod session;
+mod storage;
 
 
 fn main() {
-    dioxus::launch(ui::app);
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() { // Sem argumentos abre a interface gráfica
+        dioxus::launch(ui::app);
+        return;
+    }
+
+    if let Err(e) = cli::run(&args) { // Com argumentos roda no modo de linha de comando
+        eprintln!("Erro: {:#}", e);
+        std::process::exit(1);
+    }
+}
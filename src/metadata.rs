@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use exif::{In, Tag, Value};
+use opencv::{core, prelude::*};
+
+use crate::export::ExportFormat;
+use crate::image_process::{self, ImageError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetadataMode { // O que fazer com o EXIF na exportação
+    Preserve, // Mantém os metadados originais
+    Strip, // Remove todos os metadados
+    Edit, // Grava a versão editada na interface
+}
+
+pub const EDITABLE_TAGS: &[(Tag, &str)] = &[ // Campos de texto que podem ser editados na interface
+    (Tag::Artist, "Autor"),
+    (Tag::Copyright, "Copyright"),
+    (Tag::ImageDescription, "Descrição"),
+];
+
+const GPS_TAGS: &[Tag] = &[
+    Tag::GPSLatitudeRef,
+    Tag::GPSLatitude,
+    Tag::GPSLongitudeRef,
+    Tag::GPSLongitude,
+    Tag::GPSAltitudeRef,
+    Tag::GPSAltitude,
+    Tag::GPSTimeStamp,
+    Tag::GPSDateStamp,
+];
+
+#[derive(Debug, Clone)]
+pub struct Metadata { // Campos EXIF lidos do arquivo original
+    fields: Vec<exif::Field>,
+    little_endian: bool,
+}
+
+impl Metadata {
+    pub fn read(path: &Path) -> Option<Self> { // Lê o EXIF do arquivo (None quando não há metadados)
+        let file = std::fs::File::open(path).ok()?;
+        let exif = exif::Reader::new().read_from_container(&mut BufReader::new(file)).ok()?;
+        Some(Metadata {
+            fields: exif.fields().cloned().collect(),
+            little_endian: exif.little_endian(),
+        })
+    }
+
+    pub fn empty() -> Self { // Ponto de partida para editar imagens sem EXIF
+        Metadata { fields: Vec::new(), little_endian: true }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    fn field(&self, tag: Tag) -> Option<&exif::Field> {
+        self.fields.iter().find(|field| field.tag == tag && field.ifd_num == In::PRIMARY)
+    }
+
+    fn display(&self, tag: Tag) -> Option<String> {
+        self.field(tag).map(|field| field.display_value().with_unit(()).to_string())
+    }
+
+    pub fn orientation(&self) -> u32 { // Valor da tag Orientation (1 = normal)
+        self.field(Tag::Orientation)
+            .and_then(|field| field.value.get_uint(0))
+            .unwrap_or(1)
+    }
+
+    pub fn text(&self, tag: Tag) -> String { // Valor de um campo de texto (vazio quando não existe)
+        match self.field(tag).map(|field| &field.value) {
+            Some(Value::Ascii(lines)) => lines
+                .iter()
+                .map(|line| String::from_utf8_lossy(line).into_owned())
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        }
+    }
+
+    pub fn set_text(&mut self, tag: Tag, text: &str) { // Edita um campo de texto (texto vazio remove o campo)
+        self.fields.retain(|field| !(field.tag == tag && field.ifd_num == In::PRIMARY));
+        if !text.trim().is_empty() {
+            self.fields.push(exif::Field {
+                tag,
+                ifd_num: In::PRIMARY,
+                value: Value::Ascii(vec![text.trim().as_bytes().to_vec()]),
+            });
+        }
+    }
+
+    pub fn has_gps(&self) -> bool {
+        self.fields.iter().any(|field| GPS_TAGS.contains(&field.tag))
+    }
+
+    pub fn remove_gps(&mut self) { // Remove a localização antes de compartilhar a imagem
+        self.fields.retain(|field| !GPS_TAGS.contains(&field.tag));
+    }
+
+    pub fn summary(&self) -> Vec<(&'static str, String)> { // Campos mais úteis, já formatados para o painel de EXIF
+        let mut summary = Vec::new();
+
+        let camera = [self.display(Tag::Make), self.display(Tag::Model)]
+            .into_iter()
+            .flatten()
+            .map(|value| value.trim_matches('"').to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !camera.is_empty() {
+            summary.push(("Câmera", camera));
+        }
+
+        let fields = [
+            (Tag::LensModel, "Lente"),
+            (Tag::ExposureTime, "Exposição"),
+            (Tag::FNumber, "Abertura"),
+            (Tag::PhotographicSensitivity, "ISO"),
+            (Tag::FocalLength, "Distância focal"),
+            (Tag::DateTimeOriginal, "Data da foto"),
+            (Tag::DateTime, "Data de modificação"),
+            (Tag::Software, "Software"),
+        ];
+        for (tag, label) in fields {
+            if let Some(value) = self.display(tag) {
+                summary.push((label, value.trim_matches('"').to_string()));
+            }
+        }
+
+        if let (Some(latitude), Some(longitude)) = (self.display(Tag::GPSLatitude), self.display(Tag::GPSLongitude)) {
+            let latitude_ref = self.text(Tag::GPSLatitudeRef);
+            let longitude_ref = self.text(Tag::GPSLongitudeRef);
+            summary.push(("GPS", format!("{} {}, {} {}", latitude, latitude_ref, longitude, longitude_ref)));
+        }
+        if let Some(altitude) = self.display(Tag::GPSAltitude) {
+            summary.push(("Altitude", altitude));
+        }
+
+        summary
+    }
+
+    fn to_tiff(&self) -> anyhow::Result<Vec<u8>> { // Codifica os campos como bloco TIFF/EXIF
+        let orientation = exif::Field { // Os pixels já foram endireitados, então a orientação passa a ser normal
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![1]),
+        };
+
+        let mut seen = HashSet::new();
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&orientation);
+        seen.insert(Tag::Orientation);
+        for field in &self.fields {
+            let skip = field.ifd_num != In::PRIMARY // Miniatura não é regravada
+                || matches!(field.tag, Tag::MakerNote | Tag::PixelXDimension | Tag::PixelYDimension); // Dados que deixam de valer após a edição
+            if !skip && seen.insert(field.tag) {
+                writer.push_field(field);
+            }
+        }
+
+        let mut buffer = Cursor::new(Vec::new());
+        writer.write(&mut buffer, self.little_endian).context("codificação do EXIF")?;
+        Ok(buffer.into_inner())
+    }
+}
+
+pub fn load_oriented(path: &str) -> Result<(Mat, Option<Metadata>)> { // Carrega a imagem já endireitada, junto com o EXIF
+    let img = image_process::load_image(path)?;
+    let metadata = Metadata::read(Path::new(path));
+    let orientation = metadata.as_ref().map_or(1, Metadata::orientation);
+    Ok((normalize_orientation(&img, orientation)?, metadata))
+}
+
+pub fn normalize_orientation(img: &Mat, orientation: u32) -> Result<Mat> { // Endireita a imagem conforme a tag Orientation do EXIF
+    let mut result = Mat::default();
+    match orientation {
+        2 => core::flip(img, &mut result, 1).context("espelhamento horizontal")?,
+        3 => core::rotate(img, &mut result, core::ROTATE_180).context("rotação de 180 graus")?,
+        4 => core::flip(img, &mut result, 0).context("espelhamento vertical")?,
+        5 => core::transpose(img, &mut result).context("transposição")?,
+        6 => core::rotate(img, &mut result, core::ROTATE_90_CLOCKWISE).context("rotação de 90 graus")?,
+        7 => {
+            let mut transposed = Mat::default();
+            core::transpose(img, &mut transposed).context("transposição")?;
+            core::rotate(&transposed, &mut result, core::ROTATE_180).context("rotação de 180 graus")?;
+        }
+        8 => core::rotate(img, &mut result, core::ROTATE_90_COUNTERCLOCKWISE).context("rotação de 90 graus")?,
+        _ => result = img.clone(),
+    }
+    Ok(result)
+}
+
+pub fn embed(path: &Path, format: ExportFormat, metadata: &Metadata) -> Result<()> { // Insere o EXIF no arquivo já gravado (JPEG e PNG)
+    rewrite_with_exif(path, format, metadata).map_err(ImageError::Metadata)
+}
+
+fn rewrite_with_exif(path: &Path, format: ExportFormat, metadata: &Metadata) -> anyhow::Result<()> {
+    let bytes = std::fs::read(path).with_context(|| format!("leitura de {}", path.display()))?;
+    let bytes = match format {
+        ExportFormat::Jpeg { .. } => insert_jpeg_exif(&bytes, &metadata.to_tiff()?)?,
+        ExportFormat::Png => insert_png_exif(&bytes, &metadata.to_tiff()?)?,
+        _ => bail!("o formato {} não grava metadados EXIF", format.label()),
+    };
+    std::fs::write(path, bytes).with_context(|| format!("gravação de {}", path.display()))
+}
+
+fn insert_jpeg_exif(jpeg: &[u8], tiff: &[u8]) -> anyhow::Result<Vec<u8>> { // Segmento APP1 logo após o SOI (e o APP0/JFIF, se houver)
+    if jpeg.len() < 4 || jpeg[0..2] != [0xFF, 0xD8] {
+        bail!("arquivo JPEG inválido");
+    }
+    let segment_length = 2 + 6 + tiff.len();
+    let segment_length = u16::try_from(segment_length).context("EXIF grande demais para um segmento JPEG")?;
+
+    let mut position = 2;
+    if jpeg[2..4] == [0xFF, 0xE0] && jpeg.len() >= 6 {
+        position += 2 + usize::from(u16::from_be_bytes([jpeg[4], jpeg[5]]));
+    }
+
+    let mut output = Vec::with_capacity(jpeg.len() + tiff.len() + 10);
+    output.extend_from_slice(&jpeg[..position]);
+    output.extend_from_slice(&[0xFF, 0xE1]);
+    output.extend_from_slice(&segment_length.to_be_bytes());
+    output.extend_from_slice(b"Exif\0\0");
+    output.extend_from_slice(tiff);
+    output.extend_from_slice(&jpeg[position..]);
+    Ok(output)
+}
+
+fn insert_png_exif(png: &[u8], tiff: &[u8]) -> anyhow::Result<Vec<u8>> { // Chunk eXIf logo após o IHDR
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4; // Assinatura + chunk IHDR completo
+    if png.len() < IHDR_END || &png[12..16] != b"IHDR" {
+        bail!("arquivo PNG inválido");
+    }
+    let length = u32::try_from(tiff.len()).context("EXIF grande demais para um chunk PNG")?;
+
+    let mut chunk = Vec::with_capacity(tiff.len() + 12);
+    chunk.extend_from_slice(&length.to_be_bytes());
+    chunk.extend_from_slice(b"eXIf");
+    chunk.extend_from_slice(tiff);
+    let crc = crc32(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+
+    let mut output = Vec::with_capacity(png.len() + chunk.len());
+    output.extend_from_slice(&png[..IHDR_END]);
+    output.extend_from_slice(&chunk);
+    output.extend_from_slice(&png[IHDR_END..]);
+    Ok(output)
+}
+
+fn crc32(bytes: &[u8]) -> u32 { // CRC dos chunks PNG
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Metadata { // EXIF mínimo, com uma orientação que deve ser normalizada na gravação
+        let mut metadata = Metadata {
+            fields: vec![exif::Field { tag: Tag::Orientation, ifd_num: In::PRIMARY, value: Value::Short(vec![6]) }],
+            little_endian: false,
+        };
+        metadata.set_text(Tag::Artist, "Fulana de Tal");
+        metadata.set_text(Tag::Copyright, "CC BY 4.0");
+        metadata
+    }
+
+    fn minimal_jpeg() -> Vec<u8> { // SOI + APP0 (JFIF) + EOI: basta para os marcadores
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        jpeg.extend_from_slice(b"JFIF\0");
+        jpeg.extend_from_slice(&[0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        let crc = crc32(&chunk[4..]);
+        chunk.extend_from_slice(&crc.to_be_bytes());
+        chunk
+    }
+
+    fn minimal_png() -> Vec<u8> { // Assinatura + IHDR (1x1, RGB de 8 bits) + IEND
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0]));
+        png.extend(png_chunk(b"IEND", &[]));
+        png
+    }
+
+    fn round_trip(name: &str, bytes: &[u8], format: ExportFormat) -> Metadata {
+        let path = std::env::temp_dir().join(format!("imagevisualizer-{}-{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        embed(&path, format, &sample()).unwrap();
+        let metadata = Metadata::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        metadata.expect("o EXIF gravado deveria ser lido de volta")
+    }
+
+    #[test]
+    fn crc32_matches_the_png_iend_chunk() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn jpeg_exif_round_trips() {
+        let metadata = round_trip("exif.jpg", &minimal_jpeg(), ExportFormat::Jpeg { quality: 95 });
+        assert_eq!(metadata.text(Tag::Artist), "Fulana de Tal");
+        assert_eq!(metadata.text(Tag::Copyright), "CC BY 4.0");
+        assert_eq!(metadata.orientation(), 1); // Os pixels já saem endireitados
+    }
+
+    #[test]
+    fn png_exif_round_trips() {
+        let metadata = round_trip("exif.png", &minimal_png(), ExportFormat::Png);
+        assert_eq!(metadata.text(Tag::Artist), "Fulana de Tal");
+        assert_eq!(metadata.text(Tag::Copyright), "CC BY 4.0");
+        assert_eq!(metadata.orientation(), 1);
+    }
+
+    #[test]
+    fn jpeg_exif_goes_after_jfif() {
+        let jpeg = insert_jpeg_exif(&minimal_jpeg(), &sample().to_tiff().unwrap()).unwrap();
+        assert_eq!(jpeg[2..4], [0xFF, 0xE0]);
+        assert_eq!(jpeg[20..22], [0xFF, 0xE1]);
+        assert_eq!(&jpeg[24..30], b"Exif\0\0");
+    }
+
+    #[test]
+    fn empty_metadata_can_be_edited() { // Imagens sem EXIF também podem ganhar autor e copyright
+        let mut metadata = Metadata::empty();
+        assert!(metadata.is_empty());
+        metadata.set_text(Tag::Artist, "Fulana de Tal");
+        assert!(!metadata.is_empty());
+        let jpeg = insert_jpeg_exif(&minimal_jpeg(), &metadata.to_tiff().unwrap()).unwrap();
+        let path = std::env::temp_dir().join(format!("imagevisualizer-{}-empty.jpg", std::process::id()));
+        std::fs::write(&path, jpeg).unwrap();
+        let read = Metadata::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.expect("o EXIF novo deveria ser lido de volta").text(Tag::Artist), "Fulana de Tal");
+    }
+
+    #[test]
+    fn formats_without_exif_are_refused() { // O export não chama o embed nesses formatos; o erro evita uma perda silenciosa
+        let path = std::env::temp_dir().join(format!("imagevisualizer-{}-exif.tiff", std::process::id()));
+        std::fs::write(&path, b"II*\0").unwrap();
+        assert!(embed(&path, ExportFormat::Tiff, &sample()).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(!ExportFormat::Tiff.supports_metadata());
+        assert!(!ExportFormat::WebP { quality: 90 }.supports_metadata());
+        assert!(!ExportFormat::Bmp.supports_metadata());
+    }
+
+    #[test]
+    fn invalid_containers_are_rejected() {
+        assert!(insert_jpeg_exif(b"not a jpeg", &[]).is_err());
+        assert!(insert_png_exif(b"\x89PNG\r\n\x1a\n", &[]).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use crate::image_process::Filter;
+use crate::storage::{self, Document, Stored};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset { // Cadeia de filtros com nome, salva em um arquivo .toml (ver o formato em storage.rs)
+    pub name: String,
+    pub filters: Vec<Filter>,
+}
+
+impl Stored for Preset {
+    const KIND: &'static str = "preset";
+    const FOLDER: &'static str = "presets";
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn to_toml(&self) -> String { // Formato legível, fácil de editar e compartilhar
+        let mut text = format!("name = {}\nfilters = [\n", storage::quote(&self.name));
+        for filter in &self.filters {
+            text.push_str(&format!("    {},\n", storage::quote(&filter.to_string())));
+        }
+        text.push_str("]\n");
+        text
+    }
+
+    fn from_toml(text: &str) -> anyhow::Result<Self> {
+        let document = Document::parse(text)?;
+        let filters = document
+            .list("filters")?
+            .iter()
+            .map(|filter| filter.parse::<Filter>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Preset { name: document.text("name")?.to_string(), filters })
+    }
+}
+
+impl Preset {
+    pub fn load_file(path: &Path) -> anyhow::Result<Self> {
+        storage::load_file(path)
+    }
+
+    pub fn write_file(&self, path: &Path) -> anyhow::Result<()> {
+        storage::write_file(self, path)
+    }
+}
+
+pub fn presets_dir() -> Option<PathBuf> {
+    storage::dir::<Preset>()
+}
+
+pub fn list() -> Vec<Preset> { // Presets salvos, em ordem alfabética (arquivos inválidos são ignorados)
+    storage::list()
+}
+
+pub fn save(preset: &Preset) -> anyhow::Result<PathBuf> { // Salva (ou substitui) o preset na pasta de configuração
+    storage::save(preset)
+}
+
+pub fn delete(preset: &Preset) -> anyhow::Result<()> {
+    storage::delete(preset)
+}
+
+pub fn find(name_or_path: &str) -> anyhow::Result<Preset> { // Aceita o caminho de um arquivo ou o nome de um preset salvo
+    storage::find(name_or_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_round_trips() {
+        let preset = Preset {
+            name: "Scan \"P&B\" [v2] # final".to_string(),
+            filters: vec![
+                "grayscale".parse().unwrap(),
+                "blur:ksize=7,sigma=1.5".parse().unwrap(),
+                "rotate:angle=-3.25,mode=crop".parse().unwrap(),
+            ],
+        };
+        assert_eq!(Preset::from_toml(&preset.to_toml()).unwrap(), preset);
+
+        let empty = Preset { name: "Vazio".to_string(), filters: Vec::new() };
+        assert_eq!(Preset::from_toml(&empty.to_toml()).unwrap(), empty);
+    }
+
+    #[test]
+    fn reads_hand_written_presets() {
+        let text = "# Preset escrito à mão\nname = 'Documento'  # aspas simples\nfilters = ['grayscale', \"threshold:method=otsu\",\n    \"sharpen\"]\n";
+        let preset = Preset::from_toml(text).unwrap();
+        assert_eq!(preset.name, "Documento");
+        assert_eq!(preset.filters.iter().map(Filter::name).collect::<Vec<_>>(), ["grayscale", "threshold", "sharpen"]);
+    }
+
+    #[test]
+    fn rejects_broken_presets() {
+        assert!(Preset::from_toml("name = \"Sem filtros\"\n").is_err());
+        assert!(Preset::from_toml("filters = []\n").is_err());
+        assert!(Preset::from_toml("name = \"x\"\nfilters = [\"nao_existe\"]\n").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use opencv::prelude::*;
+
+use crate::export::{self, ExportFormat};
+use crate::history::EditStack;
+use crate::image_process::{Filter, Result};
+use crate::metadata::{self, Metadata, MetadataMode};
+
+pub struct EditSession { // Sessão de edição: mantém a imagem decodificada em memória
+    pub source_path: String, // Caminho do arquivo aberto
+    original: Mat, // Imagem original, nunca é modificada
+    current: Mat, // Resultado das operações ativas da pilha
+    stack: EditStack, // Operações aplicadas, na ordem
+    metadata: Option<Metadata>, // EXIF do arquivo original
+    edited_metadata: Metadata, // Cópia do EXIF editada na interface (vazia quando o arquivo não tem EXIF)
+}
+
+impl EditSession {
+    pub fn open(path: &str) -> Result<Self> { // Decodifica a imagem uma única vez
+        let (original, metadata) = metadata::load_oriented(path)?; // Já corrige a orientação das fotos de celular
+        Ok(EditSession {
+            source_path: path.to_string(),
+            current: original.clone(),
+            original,
+            stack: EditStack::default(),
+            edited_metadata: metadata.clone().unwrap_or_else(Metadata::empty),
+            metadata,
+        })
+    }
+
+    pub fn update(&mut self, change: impl FnOnce(&mut EditStack)) -> Result<()> { // Altera a pilha e renderiza de novo a partir da original
+        let previous = self.stack.clone(); // Se a renderização falhar, a pilha (e o histórico) volta ao estado anterior
+        change(&mut self.stack);
+        match self.stack.render(&self.original) {
+            Ok(current) => {
+                self.current = current;
+                Ok(())
+            }
+            Err(e) => {
+                self.stack = previous;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn stack(&self) -> &EditStack {
+        &self.stack
+    }
+
+    pub fn original(&self) -> &Mat {
+        &self.original
+    }
+
+    pub fn current(&self) -> &Mat {
+        &self.current
+    }
+
+    pub fn upscale_warning(&self) -> Option<String> { // Aviso quando o redimensionamento passa da resolução original (a ampliação não cria detalhes)
+        let resized = self.stack.enabled_filters().iter().any(|filter| matches!(filter, Filter::Resize { .. }));
+        let long_edge = |img: &Mat| img.cols().max(img.rows());
+        if !resized || long_edge(&self.current) <= long_edge(&self.original) {
+            return None;
+        }
+        Some(format!(
+            "A imagem foi ampliada além da resolução original ({}x{} → {}x{}); a ampliação não recupera detalhes",
+            self.original.cols(),
+            self.original.rows(),
+            self.current.cols(),
+            self.current.rows()
+        ))
+    }
+
+    pub fn precision_warning(&self) -> Option<String> { // Aviso quando filtros sem suporte a 16 bits são aplicados numa imagem de 16 bits
+        if self.original.depth() != opencv::core::CV_16U {
+            return None;
+        }
+        let labels: Vec<&str> = self
+            .stack
+            .enabled_filters()
+            .iter()
+            .filter(|filter| !filter.supports_16bit())
+            .map(Filter::label)
+            .collect();
+        if labels.is_empty() {
+            return None;
+        }
+        Some(format!("Imagem de 16 bits: estes filtros são calculados em 8 bits ({})", labels.join(", ")))
+    }
+
+    fn export_metadata(&self, mode: MetadataMode) -> Option<&Metadata> { // EXIF que vai para o arquivo exportado
+        match mode {
+            MetadataMode::Preserve => self.metadata.as_ref(),
+            MetadataMode::Strip => None,
+            MetadataMode::Edit => Some(&self.edited_metadata).filter(|metadata| !metadata.is_empty()), // Nada preenchido: nada a gravar
+        }
+    }
+
+    pub fn metadata_warning(&self, format: ExportFormat, mode: MetadataMode) -> Option<String> { // Aviso quando o formato escolhido não consegue gravar o EXIF pedido
+        if self.export_metadata(mode).is_none() || format.supports_metadata() {
+            return None;
+        }
+        Some(format!("O formato {} não grava EXIF: os metadados serão descartados (use JPEG ou PNG para mantê-los)", format.label()))
+    }
+
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
+    pub fn edited_metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.edited_metadata
+    }
+
+    pub fn edited_metadata(&self) -> &Metadata {
+        &self.edited_metadata
+    }
+
+    pub fn export(&self, output_path: &Path, format: ExportFormat, mode: MetadataMode) -> Result<PathBuf> { // Codifica e grava o resultado só quando o usuário pede
+        export::export(&self.current, output_path, format, self.export_metadata(mode))
+    }
+}
@@ -0,0 +1,487 @@
+// Arquivos de configuração salvos pelo usuário (presets, perfis de lente).
+//
+// O formato é um subconjunto pequeno do TOML, suficiente para esses arquivos:
+// - uma chave por linha: `chave = valor` (chaves com letras, números, `_` e `-`);
+// - valores: texto entre aspas duplas (escapes \\ \" \n \t \r \uXXXX), texto literal entre
+//   aspas simples, números, e listas de textos entre colchetes (que podem ocupar várias linhas);
+// - comentários com `#` fora dos textos; linhas vazias são ignoradas.
+// Tabelas, datas, textos multilinha (""" ou ''') e chaves com pontos não são aceitos.
+
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use anyhow::{bail, Context};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document { // Chaves e valores de um arquivo, na ordem em que aparecem
+    values: Vec<(String, Value)>,
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize, // Linha atual (para as mensagens de erro)
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_spaces(&mut self) { // Espaços e comentário até o fim da linha (sem consumir a quebra)
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {
+                    self.next();
+                }
+                '#' => {
+                    while self.chars.peek().is_some_and(|&c| c != '\n') {
+                        self.next();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_blank(&mut self) { // Espaços, comentários e quebras de linha
+        loop {
+            self.skip_spaces();
+            if self.chars.peek() != Some(&'\n') {
+                break;
+            }
+            self.next();
+        }
+    }
+
+    fn key(&mut self) -> anyhow::Result<String> {
+        let mut key = String::new();
+        while let Some(&c) = self.chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_' || **c == '-') {
+            key.push(c);
+            self.next();
+        }
+        if key.is_empty() {
+            bail!("linha {}: esperado `chave = valor`", self.line);
+        }
+        Ok(key)
+    }
+
+    fn escape(&mut self) -> anyhow::Result<char> { // Sequência depois da barra invertida, nos textos entre aspas duplas
+        let line = self.line;
+        match self.next() {
+            Some('\\') => Ok('\\'),
+            Some('"') => Ok('"'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('u') => {
+                let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .and_then(char::from_u32)
+                    .with_context(|| format!("linha {}: escape \\u{} inválido", line, hex))
+            }
+            Some(c) => bail!("linha {}: escape \\{} não suportado", line, c),
+            None => bail!("linha {}: texto sem as aspas de fechamento", line),
+        }
+    }
+
+    fn text(&mut self) -> anyhow::Result<String> { // Texto entre aspas duplas ou simples (a aspa de abertura ainda não foi lida)
+        let line = self.line;
+        let quote = self.next();
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some(c) if Some(c) == quote => return Ok(text),
+                Some('\\') if quote == Some('"') => text.push(self.escape()?),
+                Some('\n') | None => bail!("linha {}: texto sem as aspas de fechamento", line),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn list(&mut self) -> anyhow::Result<Vec<String>> { // Lista de textos; pode ocupar várias linhas e terminar com vírgula
+        self.next();
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            match self.chars.peek().copied() {
+                Some(']') => {
+                    self.next();
+                    return Ok(items);
+                }
+                Some('"' | '\'') => items.push(self.text()?),
+                Some(c) => bail!("linha {}: a lista só aceita textos entre aspas (encontrado '{}')", self.line, c),
+                None => bail!("lista sem o ']' de fechamento"),
+            }
+            self.skip_blank();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(items),
+                _ => bail!("linha {}: esperado ',' ou ']' na lista", self.line),
+            }
+        }
+    }
+
+    fn number(&mut self) -> anyhow::Result<f64> {
+        let mut token = String::new();
+        while let Some(&c) = self.chars.peek().filter(|c| !c.is_whitespace() && **c != '#') {
+            token.push(c);
+            self.next();
+        }
+        token
+            .replace('_', "")
+            .parse::<f64>()
+            .with_context(|| format!("linha {}: valor inválido: {}", self.line, token))
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        match self.chars.peek().copied() {
+            Some('"' | '\'') => Ok(Value::Text(self.text()?)),
+            Some('[') => Ok(Value::List(self.list()?)),
+            Some(_) => Ok(Value::Number(self.number()?)),
+            None => bail!("linha {}: valor ausente", self.line),
+        }
+    }
+}
+
+impl Document {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser { chars: text.chars().peekable(), line: 1 };
+        let mut document = Document::default();
+        loop {
+            parser.skip_blank();
+            if parser.chars.peek().is_none() {
+                return Ok(document);
+            }
+            let line = parser.line;
+            let key = parser.key()?;
+            parser.skip_spaces();
+            if parser.next() != Some('=') {
+                bail!("linha {}: esperado '=' depois de {}", line, key);
+            }
+            parser.skip_spaces();
+            let value = parser.value()?;
+            parser.skip_spaces();
+            if parser.chars.peek().is_some_and(|&c| c != '\n') {
+                bail!("linha {}: conteúdo inesperado depois do valor de {}", parser.line, key);
+            }
+            if document.get(&key).is_some() {
+                bail!("linha {}: chave {} repetida", line, key);
+            }
+            document.values.push((key, value));
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.values.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+    }
+
+    pub fn text(&self, key: &str) -> anyhow::Result<&str> {
+        match self.get(key) {
+            Some(Value::Text(text)) => Ok(text),
+            Some(_) => bail!("o campo {} precisa ser um texto", key),
+            None => bail!("campo {} ausente", key),
+        }
+    }
+
+    pub fn number(&self, key: &str) -> anyhow::Result<f64> {
+        match self.get(key) {
+            Some(Value::Number(number)) => Ok(*number),
+            Some(_) => bail!("o campo {} precisa ser um número", key),
+            None => bail!("campo {} ausente", key),
+        }
+    }
+
+    pub fn list(&self, key: &str) -> anyhow::Result<&[String]> {
+        match self.get(key) {
+            Some(Value::List(items)) => Ok(items),
+            Some(_) => bail!("o campo {} precisa ser uma lista de textos", key),
+            None => bail!("campo {} ausente", key),
+        }
+    }
+}
+
+pub fn quote(text: &str) -> String { // Texto entre aspas duplas, com os escapes lidos pelo `Document::parse`
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn slug(name: &str) -> String { // Nome do arquivo a partir do nome (vazio quando não sobra nenhuma letra ou número)
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    slug.trim_matches('_').to_string()
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+pub trait Stored: Sized { // Item salvo com nome em um arquivo .toml da pasta de configuração
+    const KIND: &'static str; // Nome usado nas mensagens ("preset", "perfil de lente")
+    const FOLDER: &'static str; // Subpasta dentro da pasta de configuração
+
+    fn name(&self) -> &str;
+    fn to_toml(&self) -> String;
+    fn from_toml(text: &str) -> anyhow::Result<Self>;
+}
+
+pub fn config_dir() -> Option<PathBuf> { // Pasta de configuração do usuário (XDG no Linux, APPDATA no Windows)
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("ImageVisualizer"))
+}
+
+pub fn dir<T: Stored>() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(T::FOLDER))
+}
+
+pub fn load_file<T: Stored>(path: &Path) -> anyhow::Result<T> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("leitura do {} {}", T::KIND, path.display()))?;
+    T::from_toml(&text).with_context(|| format!("{} inválido em {}", T::KIND, path.display()))
+}
+
+pub fn write_file<T: Stored>(item: &T, path: &Path) -> anyhow::Result<()> {
+    std::fs::write(path, item.to_toml()).with_context(|| format!("gravação do {} {}", T::KIND, path.display()))
+}
+
+fn entries<T: Stored>(dir: &Path) -> Vec<(PathBuf, T)> { // Itens da pasta com o arquivo de cada um (arquivos inválidos são ignorados)
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+        .filter_map(|path| load_file(&path).ok().map(|item| (path, item)))
+        .collect()
+}
+
+fn config_folder<T: Stored>() -> anyhow::Result<PathBuf> {
+    dir::<T>().context("pasta de configuração do usuário não encontrada")
+}
+
+pub fn list<T: Stored>() -> Vec<T> { // Itens salvos, em ordem alfabética
+    let Some(dir) = dir::<T>() else {
+        return Vec::new();
+    };
+    list_in(&dir)
+}
+
+fn list_in<T: Stored>(dir: &Path) -> Vec<T> {
+    let mut items: Vec<T> = entries(dir).into_iter().map(|(_, item)| item).collect();
+    items.sort_by_key(|item| item.name().to_lowercase());
+    items
+}
+
+pub fn save<T: Stored>(item: &T) -> anyhow::Result<PathBuf> { // Salva (ou substitui) o item na pasta de configuração
+    save_in(&config_folder::<T>()?, item)
+}
+
+fn save_in<T: Stored>(dir: &Path, item: &T) -> anyhow::Result<PathBuf> {
+    if item.name().trim().is_empty() {
+        bail!("o {} precisa de um nome", T::KIND);
+    }
+    let slug = slug(item.name());
+    if slug.is_empty() {
+        bail!("o nome do {} precisa ter pelo menos uma letra ou número", T::KIND);
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("criação da pasta {}", dir.display()))?;
+
+    let existing = entries::<T>(dir);
+    if let Some((path, _)) = existing.iter().find(|(_, other)| same_name(other.name(), item.name())) { // Mesmo nome: substitui o arquivo, onde quer que esteja
+        write_file(item, path)?;
+        return Ok(path.clone());
+    }
+    let path = dir.join(format!("{}.toml", slug));
+    if let Some((_, other)) = existing.iter().find(|(other_path, _)| *other_path == path) { // "Doc 1" e "doc-1" iriam para o mesmo arquivo
+        bail!("o nome \"{}\" usaria o mesmo arquivo do {} \"{}\"; escolha outro nome", item.name(), T::KIND, other.name());
+    }
+    if path.exists() {
+        bail!("o arquivo {} já existe e não é um {} válido", path.display(), T::KIND);
+    }
+    write_file(item, &path)?;
+    Ok(path)
+}
+
+pub fn delete<T: Stored>(item: &T) -> anyhow::Result<()> {
+    delete_in(&config_folder::<T>()?, item)
+}
+
+fn delete_in<T: Stored>(dir: &Path, item: &T) -> anyhow::Result<()> {
+    let (path, _) = entries::<T>(dir)
+        .into_iter()
+        .find(|(_, other)| same_name(other.name(), item.name()))
+        .with_context(|| format!("{} não encontrado: {}", T::KIND, item.name()))?;
+    std::fs::remove_file(&path).with_context(|| format!("remoção de {}", path.display()))
+}
+
+pub fn find<T: Stored>(name_or_path: &str) -> anyhow::Result<T> { // Aceita o caminho de um arquivo ou o nome de um item salvo
+    let path = Path::new(name_or_path);
+    if path.is_file() {
+        return load_file(path);
+    }
+    list::<T>()
+        .into_iter()
+        .find(|item| same_name(item.name(), name_or_path))
+        .with_context(|| format!("{} não encontrado: {}", T::KIND, name_or_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Note { // Item mínimo para testar a pasta
+        name: String,
+    }
+
+    impl Stored for Note {
+        const KIND: &'static str = "nota";
+        const FOLDER: &'static str = "notes";
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn to_toml(&self) -> String {
+            format!("name = {}\n", quote(&self.name))
+        }
+
+        fn from_toml(text: &str) -> anyhow::Result<Self> {
+            Ok(Note { name: Document::parse(text)?.text("name")?.to_string() })
+        }
+    }
+
+    fn note(name: &str) -> Note {
+        Note { name: name.to_string() }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf { // Pasta vazia e exclusiva do teste
+        let dir = std::env::temp_dir().join(format!("imagevisualizer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn quoted_text_round_trips() {
+        for text in ["simples", "com \"aspas\" e \\barra\\", "colchete ] e # no meio", "linha\nnova\ttab", "acentuação ✓", "\u{1}"] {
+            let document = Document::parse(&format!("name = {}\n", quote(text))).unwrap();
+            assert_eq!(document.text("name").unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn parses_the_accepted_subset() {
+        let text = r#"
+            # Comentário no início
+            name = 'literal \sem escapes'   # comentário depois do valor
+            error = 0.25
+            images = 1_000
+            filters = [
+                "blur:ksize=5",  # um por linha
+                'invert',
+                "com ] dentro",
+            ]
+            empty = []
+        "#;
+        let document = Document::parse(text).unwrap();
+        assert_eq!(document.text("name").unwrap(), r"literal \sem escapes");
+        assert_eq!(document.number("error").unwrap(), 0.25);
+        assert_eq!(document.number("images").unwrap(), 1000.0);
+        assert_eq!(document.list("filters").unwrap(), ["blur:ksize=5", "invert", "com ] dentro"]);
+        assert!(document.list("empty").unwrap().is_empty());
+        assert!(document.number("name").is_err());
+        assert!(document.text("missing").is_err());
+    }
+
+    #[test]
+    fn rejects_what_is_outside_the_subset() {
+        for text in [
+            "[tabela]\nname = \"x\"",
+            "name \"x\"",
+            "name = \"sem fim",
+            "name = \"x\" sobra",
+            "name = \"x\"\nname = \"y\"",
+            "filters = [\"a\" \"b\"]",
+            "filters = [1, 2]",
+            "filters = [\"a\"",
+            "name = \"\\q\"",
+            "a.b = 1",
+            "name =",
+        ] {
+            assert!(Document::parse(text).is_err(), "deveria recusar: {:?}", text);
+        }
+    }
+
+    #[test]
+    fn slug_keeps_letters_and_numbers() {
+        assert_eq!(slug("Doc 1"), "doc_1");
+        assert_eq!(slug("doc-1"), "doc_1");
+        assert_eq!(slug("  Foto P&B  "), "foto_p_b");
+        assert_eq!(slug("!!!"), "");
+    }
+
+    #[test]
+    fn save_refuses_names_with_the_same_file() {
+        let dir = temp_dir("storage-collision");
+        let first = save_in(&dir, &note("Doc 1")).unwrap();
+        assert!(first.ends_with("doc_1.toml"));
+
+        assert!(save_in(&dir, &note("doc-1")).is_err()); // Sobrescreveria "Doc 1"
+        assert!(save_in(&dir, &note("???")).is_err()); // Viraria ".toml"
+        assert!(save_in(&dir, &note("   ")).is_err());
+        assert_eq!(save_in(&dir, &note("DOC 1")).unwrap(), first); // Mesmo nome: substitui
+        assert_eq!(list_in::<Note>(&dir), vec![note("DOC 1")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_and_delete_follow_the_file_of_the_name() {
+        let dir = temp_dir("storage-rename");
+        std::fs::write(dir.join("importado.toml"), note("Outro nome").to_toml()).unwrap(); // Arquivo com nome diferente do slug
+
+        assert_eq!(save_in(&dir, &note("outro nome")).unwrap(), dir.join("importado.toml"));
+        save_in(&dir, &note("Segundo")).unwrap();
+        assert_eq!(list_in::<Note>(&dir), vec![note("outro nome"), note("Segundo")]);
+
+        delete_in(&dir, &note("Outro Nome")).unwrap();
+        assert_eq!(list_in::<Note>(&dir), vec![note("Segundo")]);
+        assert!(delete_in(&dir, &note("Outro Nome")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,12 +1,350 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
 use dioxus::prelude::*;
 use rfd::AsyncFileDialog;
-use crate::image_process;
+use crate::batch::{self, BatchReport};
+use crate::export::ExportFormat;
+use crate::history::EditStack;
+use crate::lenses::{self, Calibration};
+use crate::metadata::{Metadata, MetadataMode, EDITABLE_TAGS};
+use crate::presets::{self, Preset};
+use crate::image_process::{self, BorderFill, Corners, Curve, Filter, Histogram, Kernel, RotateMode, Threshold, ThresholdMethod, CURVE_CHANNELS, FULL_FRAME, KERNEL_EXAMPLES, KERNEL_KEYS};
+use crate::session::EditSession;
+
+struct Background<P, T> { // Estado compartilhado entre a thread de trabalho e a interface
+    updates: Vec<P>, // Progresso ainda não mostrado
+    result: Option<T>,
+    waker: Option<std::task::Waker>, // Acorda a tarefa do Dioxus quando chega algo novo
+}
+
+impl<P, T> Background<P, T> {
+    fn notify(shared: &Mutex<Self>, change: impl FnOnce(&mut Self)) { // Chamado pela thread de trabalho
+        let mut state = shared.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        change(&mut state);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+async fn in_background<P: Send + 'static, T: Send + 'static>( // Roda um trabalho longo (lote, calibração) fora da thread da interface, repassando o progresso
+    work: impl FnOnce(&mut dyn FnMut(P)) -> T + Send + 'static,
+    mut on_progress: impl FnMut(P),
+) -> T {
+    let shared = Arc::new(Mutex::new(Background { updates: Vec::new(), result: None, waker: None }));
+    let worker = Arc::clone(&shared);
+    std::thread::spawn(move || {
+        let worker: &Mutex<Background<P, T>> = &worker;
+        let result = work(&mut |update| Background::notify(worker, |state| state.updates.push(update)));
+        Background::notify(worker, |state| state.result = Some(result));
+    });
+
+    std::future::poll_fn(move |cx| {
+        let mut state = shared.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for update in state.updates.drain(..) {
+            on_progress(update);
+        }
+        match state.result.take() {
+            Some(result) => std::task::Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PreviewTool { // Ferramenta ativa sobre a prévia processada
+    Crop, // Arrasta um retângulo de recorte
+    Straighten, // Clica em dois pontos de uma linha que deveria ser horizontal
+    Eyedropper, // Clica num ponto que deveria ser cinza (balanço de branco)
+    Document, // Arrasta os quatro cantos de um documento para corrigir a perspectiva
+}
+
+const CROP_ASPECTS: &[(&str, &str)] = &[ // Proporções do recorte (valor do <select>, rótulo)
+    ("free", "Livre"),
+    ("1:1", "1:1"),
+    ("4:3", "4:3"),
+    ("16:9", "16:9"),
+    ("custom", "Personalizada"),
+];
+
+fn crop_aspect_ratio(aspect: &str, custom: (f64, f64)) -> Option<f64> { // Largura / altura do recorte (None = livre)
+    match aspect {
+        "1:1" => Some(1.0),
+        "4:3" => Some(4.0 / 3.0),
+        "16:9" => Some(16.0 / 9.0),
+        "custom" if custom.0 > 0.0 && custom.1 > 0.0 => Some(custom.0 / custom.1),
+        _ => None,
+    }
+}
+
+fn crop_rect(start: (f64, f64), end: (f64, f64), size: (f64, f64), aspect: Option<f64>) -> (f64, f64, f64, f64) { // Retângulo arrastado na prévia, relativo ao tamanho da imagem (0 a 1)
+    let (width, height) = size;
+    let end = (end.0.clamp(0.0, width), end.1.clamp(0.0, height));
+    let mut dx = end.0 - start.0;
+    let mut dy = end.1 - start.1;
+
+    if let Some(ratio) = aspect { // Mantém a proporção usando o lado menor do arrasto
+        let limit_x = if dx < 0.0 { start.0 } else { width - start.0 };
+        let limit_y = if dy < 0.0 { start.1 } else { height - start.1 };
+        let box_width = dx.abs().min(dy.abs() * ratio).min(limit_x).min(limit_y * ratio);
+        dx = box_width.copysign(dx);
+        dy = (box_width / ratio).copysign(dy);
+    }
+
+    let left = start.0.min(start.0 + dx);
+    let top = start.1.min(start.1 + dy);
+    (left / width, top / height, dx.abs() / width, dy.abs() / height)
+}
+
+fn histogram_path(bins: &[f32], max: f32) -> String { // Contorno do histograma em SVG (256 x 100)
+    let mut path = String::from("M0,100");
+    for (value, count) in bins.iter().enumerate() {
+        path.push_str(&format!(" L{},{:.1}", value, 100.0 - (count / max).min(1.0) * 100.0));
+    }
+    path.push_str(" L255,100 Z");
+    path
+}
+
+const CURVE_COLORS: [&str; 4] = ["#e5e7eb", "#ef4444", "#22c55e", "#3b82f6"]; // Cor de cada curva no editor (geral, R, G, B)
+
+fn curve_path(curve: &Curve) -> String { // Curva interpolada em SVG (256 x 256, com y para cima)
+    let curve = curve.prepare();
+    let mut path = String::new();
+    for value in 0..256 {
+        let command = if value == 0 { 'M' } else { 'L' };
+        path.push_str(&format!("{}{},{:.1} ", command, value, 255.0 - curve.map(f64::from(value))));
+    }
+    path
+}
+
+fn histogram_chart(histogram: &Histogram) -> Element { // Histograma RGB sobreposto, com a luminância em contorno
+    let max = [&histogram.red, &histogram.green, &histogram.blue, &histogram.luminance]
+        .iter()
+        .flat_map(|bins| bins.iter().skip(1).take(254)) // Ignora 0 e 255, que costumam ter picos de pixels estourados
+        .fold(1.0_f32, |max, count| max.max(*count));
+    rsx! {
+        svg {
+            style: "width: 100%; height: 80px; background: #111827; border-radius: 0.375rem; margin-top: 0.5rem;",
+            view_box: "0 0 256 100",
+            "preserveAspectRatio": "none",
+            path { d: histogram_path(&histogram.red, max), fill: "rgba(239, 68, 68, 0.45)" }
+            path { d: histogram_path(&histogram.green, max), fill: "rgba(34, 197, 94, 0.45)" }
+            path { d: histogram_path(&histogram.blue, max), fill: "rgba(59, 130, 246, 0.45)" }
+            path { d: histogram_path(&histogram.luminance, max), fill: "none", stroke: "#e5e7eb", stroke_width: "1" }
+        }
+    }
+}
 
 pub fn app() -> Element {
     let mut file_path = use_signal(|| None::<String>); // Caminho do arquivo selecionado
     let mut is_selecting_file = use_signal(|| false); // O arquivo está selecionado?
-    let mut current_image = use_signal(|| None::<String>); // Imagem original
-    let mut processed_image = use_signal(|| None::<String>); // Imagem processada
+    let mut session = use_signal(|| None::<EditSession>); // Imagem decodificada em memória
+    let mut current_image = use_signal(|| None::<String>); // Prévia da imagem original
+    let mut processed_image = use_signal(|| None::<String>); // Prévia da imagem processada
+    let mut error_message = use_signal(|| None::<String>); // Último erro ao aplicar um filtro
+    let mut selected_op = use_signal(|| None::<usize>); // Operação cujos parâmetros estão sendo editados
+    let mut export_format = use_signal(|| ExportFormat::Png); // Formato escolhido para o "Salvar como…"
+    let mut metadata_mode = use_signal(|| MetadataMode::Preserve); // O que fazer com o EXIF ao exportar
+    let mut saved_presets = use_signal(presets::list); // Presets salvos na pasta de configuração
+    let mut preset_name = use_signal(String::new); // Nome para salvar a cadeia atual como preset
+    let mut batch_input = use_signal(|| None::<PathBuf>); // Pasta com as imagens do lote
+    let mut batch_output = use_signal(|| None::<PathBuf>); // Pasta onde o lote é gravado
+    let mut batch_progress = use_signal(|| None::<(usize, usize)>); // Arquivo atual e total, enquanto o lote roda
+    let mut batch_report = use_signal(|| None::<BatchReport>); // Resultado do último lote
+    let mut saved_lenses = use_signal(lenses::list); // Perfis de lente salvos na pasta de configuração
+    let mut lens_input = use_signal(|| None::<PathBuf>); // Pasta com as fotos do tabuleiro
+    let mut lens_name = use_signal(String::new); // Nome do perfil a calibrar
+    let mut lens_board = use_signal(|| lenses::DEFAULT_BOARD); // Cantos internos do tabuleiro (colunas, linhas)
+    let mut lens_progress = use_signal(|| None::<(usize, usize)>); // Foto atual e total, enquanto a calibração roda
+    let mut lens_report = use_signal(|| None::<Calibration>); // Resultado da última calibração
+    let mut preview_tool = use_signal(|| None::<PreviewTool>); // Recorte ou endireitar, quando ativos
+    let mut preview_element = use_signal(|| None::<Rc<MountedData>>); // <img> da prévia processada (para medir o tamanho)
+    let mut preview_size = use_signal(|| (1.0, 1.0)); // Tamanho da prévia na tela, em pixels
+    let mut drag_start = use_signal(|| None::<(f64, f64)>); // Ponto onde o arrasto do recorte começou
+    let mut crop_selection = use_signal(|| None::<(f64, f64, f64, f64)>); // Retângulo do recorte (relativo, 0 a 1)
+    let mut crop_aspect = use_signal(|| "free".to_string()); // Proporção escolhida para o recorte
+    let mut custom_aspect = use_signal(|| (3.0, 2.0)); // Proporção personalizada (largura, altura)
+    let mut straighten_points = use_signal(Vec::<(f64, f64)>::new); // Pontos clicados com a ferramenta de endireitar
+    let mut document_corners = use_signal(|| None::<Corners>); // Cantos do documento (relativos, 0 a 1)
+    let mut dragged_corner = use_signal(|| None::<usize>); // Canto do documento sendo arrastado
+    let mut original_histogram = use_signal(|| None::<Histogram>); // Histograma da imagem original
+    let mut processed_histogram = use_signal(|| None::<Histogram>); // Histograma do resultado atual
+    let mut curve_channel = use_signal(|| 0_usize); // Curva aberta no editor (0 = geral)
+    let mut dragged_point = use_signal(|| None::<usize>); // Ponto da curva sendo arrastado
+
+    let mut show_result = move |result: image_process::Result<String>| { // Atualiza a prévia processada ou mostra o erro
+        match result {
+            Ok(preview) => {
+                processed_image.set(Some(preview));
+                error_message.set(None);
+            }
+            Err(e) => error_message.set(Some(e.to_string())), // mostra o erro real na interface
+        }
+    };
+
+    let mut edit_history = move |change: &dyn Fn(&mut EditStack)| -> bool { // Altera a pilha de edição e renderiza de novo a partir da original; diz se deu certo
+        let result = match session.write().as_mut() {
+            Some(edit) => edit.update(change).and_then(|_| {
+                processed_histogram.set(image_process::histogram(edit.current()).ok());
+                image_process::preview_data_url(edit.current())
+            }),
+            None => return false,
+        };
+        let applied = result.is_ok();
+        show_result(result);
+        applied
+    };
+
+    let mut apply_filter = move |filter: Filter| { // Adiciona o filtro no fim da pilha, sem gravar arquivos
+        let has_params = !filter.params().is_empty();
+        if edit_history(&|stack| stack.push(filter.clone())) && has_params { // já abre os sliders do filtro recém-adicionado
+            selected_op.set(session.read().as_ref().map(|edit| edit.stack().ops().len() - 1));
+        }
+    };
+
+    let mut apply_default = move |name: &str, overrides: &[(&str, f64)]| { // Botões rápidos: valores padrão do filtro, trocando só o que precisa
+        let filter = Filter::with_defaults(name).and_then(|mut filter| {
+            for (key, value) in overrides {
+                filter.set_param(key, *value)?;
+            }
+            Ok(filter)
+        });
+        match filter {
+            Ok(filter) => apply_filter(filter),
+            Err(e) => error_message.set(Some(e.to_string())),
+        }
+    };
+
+    let mut report_error = move |result: anyhow::Result<()>| { // Mostra erros de operações que não mexem na prévia
+        if let Err(e) = result {
+            error_message.set(Some(format!("{:#}", e)));
+        }
+    };
+
+    let run_batch = move || { // Aplica a cadeia de filtros ativa em todas as imagens da pasta
+        let (Some(input), Some(output)) = (batch_input(), batch_output()) else {
+            return;
+        };
+        let filters = session
+            .read()
+            .as_ref()
+            .map(|edit| edit.stack().enabled_filters())
+            .unwrap_or_default();
+        let format = export_format();
+
+        spawn(async move {
+            batch_report.set(None);
+            batch_progress.set(Some((0, 0)));
+            let report = in_background(
+                move |progress| batch::run(&input, &output, &filters, Some(format), |index, total, _| progress((index, total))),
+                move |(index, total)| batch_progress.set(Some((index, total))),
+            )
+            .await;
+            batch_progress.set(None);
+            batch_report.set(Some(report));
+        });
+    };
+
+    let run_calibration = move || { // Procura o tabuleiro em cada foto da pasta e salva o perfil da lente
+        let Some(input) = lens_input() else {
+            return;
+        };
+        let name = lens_name().trim().to_string();
+        let board = lens_board();
+
+        spawn(async move {
+            lens_report.set(None);
+            lens_progress.set(Some((0, 0)));
+            let calibration = in_background(
+                move |progress| lenses::calibrate_folder(&input, &name, board, |index, total, _| progress((index, total))),
+                move |(index, total)| lens_progress.set(Some((index, total))),
+            )
+            .await;
+            lens_progress.set(None);
+            match calibration.and_then(|calibration| lenses::save(&calibration.lens).map(|_| calibration)) {
+                Ok(calibration) => {
+                    lens_report.set(Some(calibration));
+                    saved_lenses.set(lenses::list());
+                }
+                Err(e) => error_message.set(Some(format!("{:#}", e))),
+            }
+        });
+    };
+
+    let mut set_param = move |index: usize, key: &'static str, value: f64| { // Ajusta um parâmetro e atualiza a prévia na hora
+        let filter = session
+            .read()
+            .as_ref()
+            .and_then(|edit| edit.stack().ops().get(index).map(|op| op.filter.clone()));
+        if let Some(mut filter) = filter {
+            match filter.set_param(key, value) {
+                Ok(()) => {
+                    edit_history(&|stack| stack.set_filter(index, filter.clone()));
+                }
+                Err(e) => error_message.set(Some(e.to_string())),
+            }
+        }
+    };
+
+    let mut set_curve_point = move |index: usize, curve: usize, point: usize, x: f64, y: f64| { // Move um ponto da curva (entrada e saída de uma vez, uma única renderização)
+        let filter = session
+            .read()
+            .as_ref()
+            .and_then(|edit| edit.stack().ops().get(index).map(|op| op.filter.clone()));
+        if let Some(Filter::Curves { mut curves }) = filter {
+            curves[curve].set_x(point, x);
+            curves[curve].set_y(point, y);
+            edit_history(&|stack| stack.set_filter(index, Filter::Curves { curves }));
+        }
+    };
+
+    let ops = session // Operações da pilha, para a lista do histórico
+        .read()
+        .as_ref()
+        .map(|edit| edit.stack().ops().to_vec())
+        .unwrap_or_default();
+    let op_count = ops.len();
+    let exif_summary = session // Campos EXIF do arquivo aberto
+        .read()
+        .as_ref()
+        .and_then(|edit| edit.metadata())
+        .map(Metadata::summary)
+        .unwrap_or_default();
+    let editable_fields = session // Campos de texto da cópia editável do EXIF
+        .read()
+        .as_ref()
+        .map(|edit| EDITABLE_TAGS.iter().map(|(tag, label)| (*tag, *label, edit.edited_metadata().text(*tag))).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let edited_has_gps = session
+        .read()
+        .as_ref()
+        .is_some_and(|edit| edit.edited_metadata().has_gps());
+    let preview = processed_image().or_else(|| preview_tool().and(current_image())); // As ferramentas também funcionam antes do primeiro filtro
+    let aspect_ratio = crop_aspect_ratio(&crop_aspect(), custom_aspect());
+    let warnings: Vec<String> = session // Ampliação além da resolução original e filtros calculados em 8 bits
+        .read()
+        .as_ref()
+        .map(|edit| [edit.upscale_warning(), edit.precision_warning()].into_iter().flatten().collect())
+        .unwrap_or_default();
+    let metadata_warning = session // Formato de exportação que não grava o EXIF escolhido
+        .read()
+        .as_ref()
+        .and_then(|edit| edit.metadata_warning(export_format(), metadata_mode()));
+    let selected = selected_op().and_then(|index| ops.get(index).map(|op| (index, op.filter.clone()))); // Filtro com sliders abertos
+    let selected_curves = match &selected { // Curvas usam o editor gráfico no lugar dos sliders
+        Some((_, Filter::Curves { curves })) => Some(*curves),
+        _ => None,
+    };
+    let selected_kernel = match &selected { // Kernel personalizado: valores editados numa grade
+        Some((_, Filter::Convolution { kernel })) => Some(*kernel),
+        _ => None,
+    };
 
     rsx! { // Código HTML para a interface (dioxus)
         div {
@@ -46,14 +384,105 @@ pub fn app() -> Element {
                                     if let Some(file_handle) = AsyncFileDialog::new().pick_file().await {
                                         let path = file_handle.path().display().to_string();
                                         file_path.set(Some(path.clone()));
-                                        current_image.set(Some(path));
                                         processed_image.set(None);
+                                        processed_histogram.set(None);
+                                        selected_op.set(None); // Nada da imagem anterior vale para a nova pilha
+                                        crop_selection.set(None);
+                                        document_corners.set(None);
+                                        straighten_points.set(Vec::new());
+                                        preview_tool.set(None);
+                                        match EditSession::open(&path).and_then(|edit| { // decodifica a imagem uma única vez
+                                            let preview = image_process::preview_data_url(edit.original())?;
+                                            Ok((edit, preview))
+                                        }) {
+                                            Ok((edit, preview)) => {
+                                                original_histogram.set(image_process::histogram(edit.original()).ok());
+                                                session.set(Some(edit));
+                                                current_image.set(Some(preview));
+                                                error_message.set(None);
+                                            }
+                                            Err(e) => {
+                                                session.set(None);
+                                                current_image.set(None);
+                                                original_histogram.set(None);
+                                                error_message.set(Some(e.to_string()));
+                                            }
+                                        }
                                     }
                                     is_selecting_file.set(false);
                                 }); 
                             },
                             if is_selecting_file() { "Selecionando..." } else { "Selecionar Arquivo" } 
                         }
+                        select { // Formato de exportação
+                            style: "border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.75rem; background: white; color: #374151;",
+                            onchange: move |evt| {
+                                if let Some(format) = ExportFormat::from_extension(&evt.value()) {
+                                    export_format.set(format);
+                                }
+                            },
+                            for format in ExportFormat::ALL {
+                                option {
+                                    value: format.extension(),
+                                    selected: format.extension() == export_format().extension(),
+                                    "{format.label()}"
+                                }
+                            }
+                        }
+                        if let Some(quality) = export_format().quality() { // Qualidade (só JPEG e WebP)
+                            label {
+                                style: "display: flex; align-items: center; gap: 0.5rem; color: #374151;",
+                                "Qualidade"
+                                input {
+                                    r#type: "range",
+                                    min: "1",
+                                    max: "100",
+                                    value: "{quality}",
+                                    oninput: move |evt| {
+                                        if let Ok(quality) = evt.value().parse::<i32>() {
+                                            export_format.set(export_format().with_quality(quality));
+                                        }
+                                    },
+                                }
+                                "{quality}"
+                            }
+                        }
+                        button { // Exporta o resultado (única etapa que grava no disco)
+                            style: "background: #10b981; color: white; padding: 0.75rem 1.5rem; border-radius: 0.5rem; transition: background 0.2s; cursor: pointer;",
+                            disabled: session.read().is_none(),
+                            onclick: move |_| {
+                                spawn(async move {
+                                    let format = export_format();
+                                    let default_name = session
+                                        .read()
+                                        .as_ref()
+                                        .and_then(|edit| std::path::Path::new(&edit.source_path).file_stem().map(|stem| format!("{}_processed.{}", stem.to_string_lossy(), format.extension())))
+                                        .unwrap_or_else(|| format!("image_processed.{}", format.extension()));
+                                    if let Some(file_handle) = AsyncFileDialog::new()
+                                        .add_filter(format.label(), &[format.extension()])
+                                        .set_file_name(&default_name)
+                                        .save_file()
+                                        .await
+                                    {
+                                        let result = match session.read().as_ref() {
+                                            Some(edit) => edit.export(file_handle.path(), format, metadata_mode()),
+                                            None => return,
+                                        };
+                                        if let Err(e) = result {
+                                            error_message.set(Some(e.to_string()));
+                                        }
+                                    }
+                                });
+                            },
+                            "Salvar como…"
+                        }
+                    }
+                }
+
+                if let Some(message) = error_message() { // Mostra o erro do último filtro aplicado
+                    div {
+                        style: "background: #fee2e2; color: #991b1b; border: 1px solid #fca5a5; border-radius: 0.5rem; padding: 0.75rem 1rem; margin-bottom: 2rem;",
+                        "{message}"
                     }
                 }
 
@@ -70,7 +499,7 @@ pub fn app() -> Element {
                             if let Some(path) = current_image() { // Se a imagem original estiver selecionada
                                 img { 
                                     src: path, 
-                                    style: "width: 100%; height: auto; border-radius: 0.375rem;" 
+                                    style: "width: 100%; height: auto; border-radius: 0.375rem; background: repeating-conic-gradient(#e5e7eb 0% 25%, #ffffff 0% 50%) 50% / 16px 16px;" // Xadrez aparece nas áreas transparentes
                                 }
                             } else { // Se não houver imagem selecionada
                                 div {
@@ -79,6 +508,12 @@ pub fn app() -> Element {
                                 }
                             }
                         }
+                        if let Some(histogram) = original_histogram() { // Histograma RGB e de luminância
+                            div {
+                                style: "padding: 0 1rem 1rem; background: #f9fafb;",
+                                {histogram_chart(&histogram)}
+                            }
+                        }
                     }
                     div { // Espaço para a imagem processada
                         style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); overflow: hidden;",
@@ -86,12 +521,265 @@ pub fn app() -> Element {
                             style: "background: #1f2937; color: white; padding: 0.75rem 1rem;",
                             h2 { style: "font-size: 1.125rem; font-weight: 600;", "Imagem Processada" }
                         }
+                        if session.read().is_some() {
+                            div { // Ferramentas de geometria sobre a prévia
+                                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; padding: 0.5rem 1rem; border-bottom: 1px solid #e5e7eb; color: #374151;",
+                                button {
+                                    style: if preview_tool() == Some(PreviewTool::Crop) { "background: #3b82f6; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" } else { "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" },
+                                    onclick: move |_| {
+                                        let active = preview_tool() == Some(PreviewTool::Crop);
+                                        preview_tool.set(if active { None } else { Some(PreviewTool::Crop) });
+                                        crop_selection.set(None);
+                                    },
+                                    "Recortar"
+                                }
+                                button {
+                                    style: if preview_tool() == Some(PreviewTool::Straighten) { "background: #3b82f6; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" } else { "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" },
+                                    onclick: move |_| {
+                                        let active = preview_tool() == Some(PreviewTool::Straighten);
+                                        preview_tool.set(if active { None } else { Some(PreviewTool::Straighten) });
+                                        straighten_points.set(Vec::new());
+                                    },
+                                    "Endireitar"
+                                }
+                                button {
+                                    style: if preview_tool() == Some(PreviewTool::Eyedropper) { "background: #3b82f6; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" } else { "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" },
+                                    onclick: move |_| {
+                                        let active = preview_tool() == Some(PreviewTool::Eyedropper);
+                                        preview_tool.set(if active { None } else { Some(PreviewTool::Eyedropper) });
+                                    },
+                                    "Conta-gotas"
+                                }
+                                button {
+                                    style: if preview_tool() == Some(PreviewTool::Document) { "background: #3b82f6; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" } else { "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" },
+                                    onclick: move |_| {
+                                        let active = preview_tool() == Some(PreviewTool::Document);
+                                        preview_tool.set(if active { None } else { Some(PreviewTool::Document) });
+                                        document_corners.set(None);
+                                        if !active { // Começa pelo maior quadrilátero encontrado (ou pela imagem inteira)
+                                            let detected = session.read().as_ref().map(|edit| image_process::detect_document(edit.current()));
+                                            match detected {
+                                                Some(Ok(corners)) => document_corners.set(Some(corners.unwrap_or(FULL_FRAME))),
+                                                Some(Err(e)) => error_message.set(Some(e.to_string())),
+                                                None => {}
+                                            }
+                                        }
+                                    },
+                                    "Documento"
+                                }
+                                if preview_tool() == Some(PreviewTool::Crop) {
+                                    select { // Proporção do recorte
+                                        style: "border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem; background: white;",
+                                        onchange: move |evt| {
+                                            crop_aspect.set(evt.value());
+                                            crop_selection.set(None);
+                                        },
+                                        for (value, aspect_label) in CROP_ASPECTS {
+                                            option { value: *value, selected: crop_aspect() == *value, "{aspect_label}" }
+                                        }
+                                    }
+                                    if crop_aspect() == "custom" {
+                                        input {
+                                            style: "width: 4rem; border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem;",
+                                            r#type: "number",
+                                            min: "1",
+                                            value: "{custom_aspect().0}",
+                                            oninput: move |evt| {
+                                                if let Ok(width) = evt.value().parse::<f64>() {
+                                                    custom_aspect.set((width, custom_aspect().1));
+                                                }
+                                            },
+                                        }
+                                        ":"
+                                        input {
+                                            style: "width: 4rem; border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem;",
+                                            r#type: "number",
+                                            min: "1",
+                                            value: "{custom_aspect().1}",
+                                            oninput: move |evt| {
+                                                if let Ok(height) = evt.value().parse::<f64>() {
+                                                    custom_aspect.set((custom_aspect().0, height));
+                                                }
+                                            },
+                                        }
+                                    }
+                                    button {
+                                        style: "background: #10b981; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                        disabled: crop_selection().is_none(),
+                                        onclick: move |_| {
+                                            if let Some((x, y, width, height)) = crop_selection() {
+                                                apply_filter(Filter::Crop { x, y, width, height });
+                                                crop_selection.set(None);
+                                                preview_tool.set(None);
+                                            }
+                                        },
+                                        "Aplicar recorte"
+                                    }
+                                }
+                                if preview_tool() == Some(PreviewTool::Eyedropper) {
+                                    span {
+                                        style: "color: #6b7280;",
+                                        "Clique num ponto que deveria ser branco ou cinza"
+                                    }
+                                }
+                                if preview_tool() == Some(PreviewTool::Document) {
+                                    span {
+                                        style: "color: #6b7280;",
+                                        "Arraste os cantos até as bordas do documento"
+                                    }
+                                    button {
+                                        style: "background: #10b981; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                        disabled: document_corners().is_none(),
+                                        onclick: move |_| {
+                                            if let Some(corners) = document_corners() {
+                                                apply_filter(Filter::Perspective { corners });
+                                                document_corners.set(None);
+                                                preview_tool.set(None);
+                                            }
+                                        },
+                                        "Aplicar perspectiva"
+                                    }
+                                    button {
+                                        style: "background: #10b981; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                        disabled: document_corners().is_none(),
+                                        onclick: move |_| { // Perspectiva seguida de limiar adaptativo, com cara de documento escaneado
+                                            if let Some(corners) = document_corners() {
+                                                let scanned = [
+                                                    Filter::Perspective { corners },
+                                                    Filter::Threshold { threshold: Threshold { method: ThresholdMethod::AdaptiveGaussian, block_size: 21, ..Threshold::default() } },
+                                                ];
+                                                if edit_history(&|stack| stack.extend(&scanned)) { // Um único passo de desfazer e uma única renderização
+                                                    selected_op.set(session.read().as_ref().map(|edit| edit.stack().ops().len() - 1)); // Abre os parâmetros do limiar
+                                                    document_corners.set(None);
+                                                    preview_tool.set(None);
+                                                } // Se falhar, os cantos ficam na tela para ajustar e tentar de novo
+                                            }
+                                        },
+                                        "Aplicar como escaneado"
+                                    }
+                                }
+                                if preview_tool() == Some(PreviewTool::Straighten) {
+                                    span {
+                                        style: "color: #6b7280;",
+                                        if straighten_points().is_empty() { "Clique no início de uma linha que deveria ser reta" } else { "Clique no fim da linha" }
+                                    }
+                                }
+                            }
+                        }
                         div { 
                             style: "padding: 1rem; background: #f9fafb; min-height: 300px; display: flex; align-items: center; justify-content: center;",
-                            if let Some(path) = processed_image() {
-                                img { 
-                                    src: path, 
-                                    style: "width: 100%; height: auto; border-radius: 0.375rem;" 
+                            if let Some(path) = preview {
+                                div {
+                                    style: "position: relative; width: 100%; line-height: 0;",
+                                    img { 
+                                        src: path, 
+                                        style: if preview_tool().is_some() { "width: 100%; height: auto; border-radius: 0.375rem; background: repeating-conic-gradient(#e5e7eb 0% 25%, #ffffff 0% 50%) 50% / 16px 16px; cursor: crosshair; user-select: none;" } else { "width: 100%; height: auto; border-radius: 0.375rem; background: repeating-conic-gradient(#e5e7eb 0% 25%, #ffffff 0% 50%) 50% / 16px 16px;" },
+                                        draggable: "false",
+                                        onmounted: move |evt| preview_element.set(Some(evt.data())),
+                                        onmousedown: move |evt| async move {
+                                            let Some(tool) = preview_tool() else {
+                                                return;
+                                            };
+                                            let point = evt.element_coordinates();
+                                            let point = (point.x, point.y);
+                                            if let Some(element) = preview_element() { // mede a prévia, que muda de tamanho com a janela
+                                                if let Ok(rect) = element.get_client_rect().await {
+                                                    preview_size.set((rect.size.width.max(1.0), rect.size.height.max(1.0)));
+                                                }
+                                            }
+                                            match tool {
+                                                PreviewTool::Crop => {
+                                                    drag_start.set(Some(point));
+                                                    crop_selection.set(None);
+                                                }
+                                                PreviewTool::Eyedropper => { // Amostra a cor do resultado atual e neutraliza a dominante
+                                                    let (width, height) = preview_size();
+                                                    let sample = session
+                                                        .read()
+                                                        .as_ref()
+                                                        .map(|edit| image_process::sample_color(edit.current(), point.0 / width, point.1 / height));
+                                                    match sample {
+                                                        Some(Ok((red, green, blue))) => {
+                                                            preview_tool.set(None);
+                                                            apply_filter(Filter::NeutralPoint { red, green, blue });
+                                                        }
+                                                        Some(Err(e)) => error_message.set(Some(e.to_string())),
+                                                        None => {}
+                                                    }
+                                                }
+                                                PreviewTool::Document => { // Pega o canto mais próximo do clique
+                                                    let (width, height) = preview_size();
+                                                    let nearest = document_corners().and_then(|corners| {
+                                                        corners
+                                                            .iter()
+                                                            .enumerate()
+                                                            .map(|(corner, (x, y))| (corner, (x * width - point.0).hypot(y * height - point.1)))
+                                                            .min_by(|a, b| a.1.total_cmp(&b.1))
+                                                    });
+                                                    if let Some((corner, distance)) = nearest {
+                                                        if distance <= 20.0 {
+                                                            dragged_corner.set(Some(corner));
+                                                        }
+                                                    }
+                                                }
+                                                PreviewTool::Straighten => {
+                                                    straighten_points.write().push(point);
+                                                    let points = straighten_points();
+                                                    if let [start, end] = points[..] {
+                                                        let angle = image_process::straighten_angle(start.0, start.1, end.0, end.1);
+                                                        straighten_points.set(Vec::new());
+                                                        preview_tool.set(None);
+                                                        apply_filter(Filter::Rotate { angle, mode: RotateMode::Crop, border: BorderFill::Black });
+                                                    }
+                                                }
+                                            }
+                                        },
+                                        onmousemove: move |evt| {
+                                            if let Some(start) = drag_start() {
+                                                let point = evt.element_coordinates();
+                                                let rect = crop_rect(start, (point.x, point.y), preview_size(), aspect_ratio);
+                                                crop_selection.set((rect.2 > 0.0 && rect.3 > 0.0).then_some(rect));
+                                            }
+                                            if let (Some(corner), Some(mut corners)) = (dragged_corner(), document_corners()) {
+                                                let point = evt.element_coordinates();
+                                                let (width, height) = preview_size();
+                                                corners[corner] = ((point.x / width).clamp(0.0, 1.0), (point.y / height).clamp(0.0, 1.0));
+                                                document_corners.set(Some(corners));
+                                            }
+                                        },
+                                        onmouseup: move |_| {
+                                            drag_start.set(None);
+                                            dragged_corner.set(None);
+                                        },
+                                    }
+                                    if let Some((x, y, width, height)) = crop_selection() { // Retângulo do recorte (não recebe cliques)
+                                        div {
+                                            style: "position: absolute; left: {x * 100.0}%; top: {y * 100.0}%; width: {width * 100.0}%; height: {height * 100.0}%; border: 2px dashed #ffffff; box-shadow: 0 0 0 9999px rgba(0,0,0,0.45); pointer-events: none;",
+                                        }
+                                    }
+                                    if let (Some(PreviewTool::Document), Some(corners)) = (preview_tool(), document_corners()) { // Contorno do documento e alças dos cantos (não recebem cliques)
+                                        svg {
+                                            style: "position: absolute; inset: 0; width: 100%; height: 100%; pointer-events: none;",
+                                            view_box: "0 0 100 100",
+                                            preserve_aspect_ratio: "none",
+                                            polygon {
+                                                points: corners.iter().map(|(x, y)| format!("{},{}", x * 100.0, y * 100.0)).collect::<Vec<_>>().join(" "),
+                                                fill: "rgba(59,130,246,0.15)",
+                                                stroke: "#3b82f6",
+                                                stroke_width: "0.4",
+                                            }
+                                        }
+                                        for (x, y) in corners {
+                                            div {
+                                                style: "position: absolute; left: calc({x * 100.0}% - 6px); top: calc({y * 100.0}% - 6px); width: 12px; height: 12px; border-radius: 50%; background: #3b82f6; border: 2px solid #ffffff; pointer-events: none;",
+                                            }
+                                        }
+                                    }
+                                    for (point_x, point_y) in straighten_points() { // Primeiro ponto da linha de endireitar
+                                        div {
+                                            style: "position: absolute; left: {point_x - 4.0}px; top: {point_y - 4.0}px; width: 8px; height: 8px; border-radius: 50%; background: #ef4444; pointer-events: none;",
+                                        }
+                                    }
                                 }
                             } else { 
                                 div {
@@ -100,6 +788,88 @@ pub fn app() -> Element {
                                 }
                             }
                         }
+                        if let Some(histogram) = processed_histogram() { // Histograma RGB e de luminância
+                            div {
+                                style: "padding: 0 1rem 1rem; background: #f9fafb;",
+                                {histogram_chart(&histogram)}
+                            }
+                        }
+                    }
+                }
+
+                if session.read().is_some() {
+                    div { // Painel de metadados EXIF
+                        style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 1.5rem; margin-bottom: 2rem;",
+                        h3 {
+                            style: "font-size: 1.25rem; font-weight: 600; color: #1f2937; margin-bottom: 1rem;",
+                            "Metadados (EXIF)"
+                        }
+                        if exif_summary.is_empty() {
+                            div {
+                                style: "color: #9ca3af;",
+                                "Esta imagem não tem metadados EXIF"
+                            }
+                        }
+                        div {
+                            style: "display: grid; grid-template-columns: 12rem 1fr; gap: 0.25rem 1rem; margin-bottom: 1rem;",
+                            for (field_label, value) in exif_summary {
+                                span { key: "{field_label}-label", style: "color: #6b7280;", "{field_label}" }
+                                span { key: "{field_label}-value", style: "color: #1f2937;", "{value}" }
+                            }
+                        }
+                        div {
+                            style: "display: flex; align-items: center; gap: 1rem; color: #374151;",
+                            "Ao exportar:"
+                            for (mode, mode_label) in [(MetadataMode::Preserve, "Manter"), (MetadataMode::Strip, "Remover"), (MetadataMode::Edit, "Editar")] {
+                                label {
+                                    key: "{mode_label}",
+                                    style: "display: flex; align-items: center; gap: 0.25rem;",
+                                    input {
+                                        r#type: "radio",
+                                        name: "metadata_mode",
+                                        checked: metadata_mode() == mode,
+                                        onchange: move |_| metadata_mode.set(mode),
+                                    }
+                                    "{mode_label}"
+                                }
+                            }
+                        }
+                        if let Some(warning) = metadata_warning {
+                            div {
+                                style: "margin-top: 1rem; background: #fef3c7; color: #92400e; border: 1px solid #fcd34d; border-radius: 0.5rem; padding: 0.5rem 1rem;",
+                                "{warning}"
+                            }
+                        }
+                        if metadata_mode() == MetadataMode::Edit {
+                            div {
+                                style: "display: grid; grid-template-columns: 12rem 1fr; gap: 0.5rem 1rem; align-items: center; margin-top: 1rem;",
+                                for (tag, field_label, value) in editable_fields {
+                                    label { key: "{field_label}-label", style: "color: #374151;", "{field_label}" }
+                                    input {
+                                        key: "{field_label}-input",
+                                        style: "border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem 0.5rem;",
+                                        r#type: "text",
+                                        value: "{value}",
+                                        oninput: move |evt| {
+                                            if let Some(edit) = session.write().as_mut() {
+                                                edit.edited_metadata_mut().set_text(tag, &evt.value());
+                                            }
+                                        },
+                                    }
+                                }
+                            }
+                            if edited_has_gps {
+                                button {
+                                    style: "margin-top: 1rem; background: #fee2e2; color: #991b1b; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                                    onclick: move |_| {
+                                        if let Some(edit) = session.write().as_mut() {
+                                            edit.edited_metadata_mut().remove_gps();
+                                        }
+                                    },
+                                    "Remover localização (GPS)"
+                                }
+                            }
+                        }
                     }
                 }
 
@@ -113,156 +883,733 @@ pub fn app() -> Element {
                         style: "display: grid; grid-template-columns: repeat(3, 1fr); gap: 1rem;",
                         button { // Botão para o filtro de escala de cinza
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "grayscale".to_string()); // chama a função de escala de cinza em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de escala de cinza
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("grayscale", &[]),
                             "Escala de cinza"
                         }
                         button { // Botão para o filtro de inversão de cores
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "invert".to_string()); // chama a função de inversão de cores em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de inversão de cores
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("invert", &[]),
                             "Inversão de cores"
                         }
                         button { // Botão para o filtro de aumento de contraste
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "contrast".to_string()); // chama a função de aumento de contraste em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de aumento de contraste
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("contrast", &[]),
                             "Aumento de contraste"
                         }
                         button { // Botão para o filtro de desfoque
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "blur".to_string()); // chama a função de desfoque em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de desfoque
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("blur", &[]),
                             "Desfoque (blur)"
                         }
                         button { // Botão para o filtro de nitidez
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "sharpen".to_string()); // chama a função de nitidez em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de nitidez
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("sharpen", &[]),
                             "Nitidez (sharpen)"
                         }
                         button { // Botão para o filtro de detecção de bordas
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "edges".to_string()); // chama a função de detecção de bordas em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de detecção de bordas
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("edges", &[]),
                             "Detecção de bordas"
                         }
                         button { // Botão para o filtro de redução de tamanho
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "resize_half".to_string()); // chama a função de redução de tamanho em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de redução de tamanho
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("resize", &[]),
                             "Redução de Resolução"
                         }
                         button { // Botão para o filtro de rotação de 90 graus no sentido horário
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
-                            onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
-                                    spawn(async move {
-                                        let result = image_process::add_filter(path, "rotate_90_cw".to_string()); // chama a função de rotação de 90 graus no sentido horário em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de rotação de 90 graus no sentido horário
-                                    });
-                                }
-                            },
+                            onclick: move |_| apply_default("rotate_90_cw", &[]),
                             "Rotação de 90 graus no sentido horário"
                         }
                         button { // Botão para o filtro de rotação de 90 graus no sentido anti-horário
                             style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("rotate_90_ccw", &[]),
+                            "Rotação de 90 graus no sentido anti-horário"
+                        }
+                        button { // Botão para a rotação livre (o ângulo é ajustado nos parâmetros)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("rotate", &[("angle", 15.0)]),
+                            "Rotação livre"
+                        }
+                        button { // Botão para espelhar na horizontal
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("flip_h", &[]),
+                            "Espelhar na horizontal"
+                        }
+                        button { // Botão para espelhar na vertical
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("flip_v", &[]),
+                            "Espelhar na vertical"
+                        }
+                        button { // Botão para os níveis (ponto preto, ponto branco e gama)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("levels", &[]),
+                            "Níveis"
+                        }
+                        button { // Botão para as curvas de tons
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("curves", &[]),
+                            "Curvas"
+                        }
+                        button { // Botão para a equalização do histograma
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("equalize", &[]),
+                            "Equalização do histograma"
+                        }
+                        button { // Botão para o contraste local (CLAHE)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("clahe", &[]),
+                            "Contraste local (CLAHE)"
+                        }
+                        button { // Botão para ver um canal isolado
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("channel", &[]),
+                            "Ver canal"
+                        }
+                        button { // Botão para trocar os canais de cor
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("swap_channels", &[]),
+                            "Trocar canais"
+                        }
+                        button { // Botão para matiz, saturação e luminosidade
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("hue_saturation", &[]),
+                            "Matiz e saturação"
+                        }
+                        button { // Botão para ajustar um canal em HSV/HLS/Lab/YCrCb
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("channel_adjust", &[]),
+                            "Ajuste de canal"
+                        }
+                        button { // Botão para o balanço de branco automático
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("white_balance", &[]),
+                            "Balanço de branco automático"
+                        }
+                        button { // Botão para temperatura e matiz manuais
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("temperature", &[]),
+                            "Temperatura e matiz"
+                        }
+                        button { // Botão para o filtro mediano
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("median", &[]),
+                            "Filtro mediano"
+                        }
+                        button { // Botão para o filtro bilateral
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("bilateral", &[]),
+                            "Filtro bilateral"
+                        }
+                        button { // Botão para a remoção de ruído (non-local means)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("nlmeans", &[]),
+                            "Remover ruído (NL-means)"
+                        }
+                        button { // Botão para a máscara de nitidez (raio, intensidade e limiar)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("unsharp", &[]),
+                            "Máscara de nitidez"
+                        }
+                        button { // Botão para o kernel de convolução personalizado
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("kernel", &[]),
+                            "Kernel personalizado"
+                        }
+                        button { // Botão para as operações morfológicas (erosão, dilatação, abertura...)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("morphology", &[]),
+                            "Morfologia"
+                        }
+                        button { // Botão para a limiarização (imagem binária)
+                            style: "background: linear-gradient(to right, #374151, #1f2937); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer;",
+                            onclick: move |_| apply_default("threshold", &[]),
+                            "Limiarização"
+                        }
+                        button { // Botão para o filtro de reset
+                            style: "background: linear-gradient(to right, #dc2626, #991b1b); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer; text-align: center; align-items: center; justify-content: center;",
+                            onclick: move |_| { // volta para a imagem original (pode ser desfeito)
+                                edit_history(&|stack| stack.clear());
+                                selected_op.set(None);
+                            },
+                            "Reset"
+                        }
+                    }
+                    for warning in warnings {
+                        div {
+                            style: "margin-top: 1rem; background: #fef3c7; color: #92400e; border: 1px solid #fcd34d; border-radius: 0.5rem; padding: 0.5rem 1rem;",
+                            "{warning}"
+                        }
+                    }
+                    if let Some((index, filter)) = selected { // Parâmetros da operação selecionada (prévia ao vivo)
+                        div {
+                            style: "margin-top: 1.5rem; padding-top: 1rem; border-top: 1px solid #e5e7eb;",
+                            h4 {
+                                style: "font-weight: 600; color: #1f2937; margin-bottom: 0.75rem;",
+                                "Parâmetros: {filter.label()}"
+                            }
+                            if filter.params().is_empty() {
+                                div {
+                                    style: "color: #9ca3af;",
+                                    "Este filtro não tem parâmetros"
+                                }
+                            }
+                            if let Some(curves) = selected_curves { // Editor gráfico das curvas (arraste os pontos)
+                                div {
+                                    style: "display: flex; gap: 1.5rem; align-items: flex-start;",
+                                    div {
+                                        style: "display: flex; flex-direction: column; gap: 0.5rem;",
+                                        for (curve_index, curve_label) in CURVE_CHANNELS.iter().enumerate() {
+                                            button {
+                                                key: "{curve_label}",
+                                                style: if curve_channel() == curve_index { "background: #3b82f6; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" } else { "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;" },
+                                                onclick: move |_| curve_channel.set(curve_index),
+                                                "{curve_label}"
+                                            }
+                                        }
+                                        button {
+                                            style: "background: #fee2e2; color: #991b1b; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                            onclick: move |_| {
+                                                let mut reset = curves;
+                                                reset[curve_channel()] = Curve::default();
+                                                edit_history(&|stack| stack.set_filter(index, Filter::Curves { curves: reset }));
+                                            },
+                                            "Restaurar curva"
+                                        }
+                                    }
+                                    div { // Área de 256 x 256 pixels: cada pixel é um valor de tom
+                                        style: "position: relative; width: 256px; height: 256px; cursor: crosshair; user-select: none;",
+                                        onmousedown: move |evt| {
+                                            let mouse = evt.element_coordinates();
+                                            let nearest = curves[curve_channel()]
+                                                .points
+                                                .iter()
+                                                .enumerate()
+                                                .map(|(point, (x, y))| (point, (x - mouse.x).hypot(255.0 - y - mouse.y)))
+                                                .min_by(|a, b| a.1.total_cmp(&b.1));
+                                            if let Some((point, distance)) = nearest {
+                                                if distance <= 12.0 {
+                                                    dragged_point.set(Some(point));
+                                                }
+                                            }
+                                        },
+                                        onmousemove: move |evt| {
+                                            if let Some(point) = dragged_point() {
+                                                let mouse = evt.element_coordinates();
+                                                set_curve_point(index, curve_channel(), point, mouse.x, 255.0 - mouse.y);
+                                            }
+                                        },
+                                        onmouseup: move |_| dragged_point.set(None),
+                                        onmouseleave: move |_| dragged_point.set(None),
+                                        svg {
+                                            style: "position: absolute; inset: 0; width: 256px; height: 256px; background: #111827; border-radius: 0.375rem; pointer-events: none;",
+                                            view_box: "0 0 256 256",
+                                            path { d: "M64,0 V256 M128,0 V256 M192,0 V256 M0,64 H256 M0,128 H256 M0,192 H256", stroke: "#374151", stroke_width: "1" }
+                                            path { d: "M0,255 L255,0", stroke: "#4b5563", stroke_width: "1", stroke_dasharray: "4 4" }
+                                            path { d: curve_path(&curves[curve_channel()]), fill: "none", stroke: CURVE_COLORS[curve_channel()], stroke_width: "2" }
+                                            for (x, y) in curves[curve_channel()].points {
+                                                circle { cx: "{x}", cy: "{255.0 - y}", r: "5", fill: CURVE_COLORS[curve_channel()] }
+                                            }
+                                        }
+                                    }
+                                }
+                            } else {
+                                for param in filter.params().into_iter().filter(|param| !image_process::is_kernel_key(param.key)) {
+                                    div {
+                                        key: "{param.key}",
+                                        style: "display: grid; grid-template-columns: 12rem 1fr 6rem; align-items: center; gap: 1rem; margin-bottom: 0.5rem;",
+                                        label { style: "color: #374151;", "{param.label}" }
+                                        if !param.options.is_empty() { // Parâmetro de escolha
+                                            select {
+                                                style: "grid-column: span 2; border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem 0.5rem; background: white;",
+                                                onchange: move |evt| {
+                                                    if let Ok(value) = evt.value().parse::<f64>() {
+                                                        set_param(index, param.key, value);
+                                                    }
+                                                },
+                                                for (option_index, (_, option_label)) in param.options.iter().enumerate() {
+                                                    option {
+                                                        value: "{option_index}",
+                                                        selected: param.value.round() as usize == option_index,
+                                                        "{option_label}"
+                                                    }
+                                                }
+                                            }
+                                        } else {
+                                            input {
+                                                r#type: "range",
+                                                min: "{param.min}",
+                                                max: "{param.max}",
+                                                step: "{param.step}",
+                                                value: "{param.value}",
+                                                oninput: move |evt| {
+                                                    if let Ok(value) = evt.value().parse::<f64>() {
+                                                        set_param(index, param.key, value);
+                                                    }
+                                                },
+                                            }
+                                            input {
+                                                style: "border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem 0.5rem;",
+                                                r#type: "number",
+                                                min: "{param.min}",
+                                                max: "{param.max}",
+                                                step: "{param.step}",
+                                                value: "{param.value}",
+                                                oninput: move |evt| {
+                                                    if let Ok(value) = evt.value().parse::<f64>() {
+                                                        set_param(index, param.key, value);
+                                                    }
+                                                },
+                                            }
+                                        }
+                                    }
+                                }
+                                if let Some(kernel) = selected_kernel { // Grade com os valores do kernel
+                                    div {
+                                        style: "display: flex; gap: 1.5rem; align-items: flex-start; margin-top: 1rem;",
+                                        div {
+                                            style: "display: grid; grid-template-columns: repeat({kernel.cols}, 4.5rem); gap: 0.25rem;",
+                                            for (row, keys) in KERNEL_KEYS.iter().enumerate().take(kernel.rows) {
+                                                for (col, key) in keys.iter().copied().enumerate().take(kernel.cols) {
+                                                    input {
+                                                        key: "{key}",
+                                                        style: "border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem 0.5rem; text-align: right;",
+                                                        r#type: "number",
+                                                        step: "0.1",
+                                                        value: "{kernel.values[row][col]}",
+                                                        oninput: move |evt| {
+                                                            if let Ok(value) = evt.value().parse::<f64>() {
+                                                                set_param(index, key, value);
+                                                            }
+                                                        },
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        div {
+                                            style: "display: flex; flex-direction: column; gap: 0.5rem;",
+                                            span { style: "color: #6b7280;", "Exemplos (3x3):" }
+                                            for (example, (example_label, _)) in KERNEL_EXAMPLES.iter().enumerate() {
+                                                button {
+                                                    key: "{example_label}",
+                                                    style: "background: #e5e7eb; color: #1f2937; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                                    onclick: move |_| {
+                                                        let example = Kernel { border: kernel.border, ..Kernel::example(example) };
+                                                        edit_history(&|stack| stack.set_filter(index, Filter::Convolution { kernel: example }));
+                                                    },
+                                                    "{example_label}"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div { // Pilha de edição: operações aplicadas, na ordem
+                    style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 1.5rem; margin-top: 2rem;",
+                    div {
+                        style: "display: flex; align-items: center; justify-content: space-between; margin-bottom: 1rem;",
+                        h3 {
+                            style: "font-size: 1.25rem; font-weight: 600; color: #1f2937;",
+                            "Histórico de edição"
+                        }
+                        div {
+                            style: "display: flex; gap: 0.5rem;",
+                            button {
+                                style: "background: #374151; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                                disabled: !session.read().as_ref().is_some_and(|edit| edit.stack().can_undo()),
+                                onclick: move |_| {
+                                    edit_history(&|stack| stack.undo());
+                                },
+                                "Desfazer"
+                            }
+                            button {
+                                style: "background: #374151; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                                disabled: !session.read().as_ref().is_some_and(|edit| edit.stack().can_redo()),
+                                onclick: move |_| {
+                                    edit_history(&|stack| stack.redo());
+                                },
+                                "Refazer"
+                            }
+                        }
+                    }
+                    if ops.is_empty() {
+                        div {
+                            style: "color: #9ca3af;",
+                            "Nenhuma operação aplicada"
+                        }
+                    }
+                    for (index, op) in ops.into_iter().enumerate() {
+                        div {
+                            key: "{index}",
+                            style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem 0; border-bottom: 1px solid #e5e7eb;",
+                            input { // Ativa ou desativa a operação
+                                r#type: "checkbox",
+                                checked: op.enabled,
+                                onchange: move |_| {
+                                    edit_history(&move |stack| stack.toggle(index));
+                                },
+                            }
+                            span { // Clique para abrir os parâmetros da operação
+                                style: if op.enabled { "flex: 1; color: #1f2937; cursor: pointer;" } else { "flex: 1; color: #9ca3af; text-decoration: line-through; cursor: pointer;" },
+                                font_weight: if selected_op() == Some(index) { "600" } else { "400" },
+                                onclick: move |_| selected_op.set(Some(index)),
+                                "{index + 1}. {op.filter.label()}"
+                            }
+                            button {
+                                style: "padding: 0.25rem 0.5rem; border-radius: 0.375rem; background: #e5e7eb; cursor: pointer;",
+                                disabled: index == 0,
+                                onclick: move |_| {
+                                    edit_history(&move |stack| stack.move_op(index, index - 1));
+                                    selected_op.set(None);
+                                },
+                                "↑"
+                            }
+                            button {
+                                style: "padding: 0.25rem 0.5rem; border-radius: 0.375rem; background: #e5e7eb; cursor: pointer;",
+                                disabled: index + 1 == op_count,
+                                onclick: move |_| {
+                                    edit_history(&move |stack| stack.move_op(index, index + 1));
+                                    selected_op.set(None);
+                                },
+                                "↓"
+                            }
+                            button {
+                                style: "padding: 0.25rem 0.5rem; border-radius: 0.375rem; background: #fee2e2; color: #991b1b; cursor: pointer;",
+                                onclick: move |_| {
+                                    edit_history(&move |stack| stack.remove(index));
+                                    selected_op.set(None);
+                                },
+                                "✕"
+                            }
+                        }
+                    }
+                }
+
+                div { // Presets: cadeias de filtros salvas com nome
+                    style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 1.5rem; margin-top: 2rem;",
+                    h3 {
+                        style: "font-size: 1.25rem; font-weight: 600; color: #1f2937; margin-bottom: 1rem;",
+                        "Presets"
+                    }
+                    div {
+                        style: "display: flex; gap: 0.5rem; margin-bottom: 1rem;",
+                        input {
+                            style: "flex: 1; border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.5rem;",
+                            r#type: "text",
+                            value: "{preset_name}",
+                            placeholder: "Nome do preset...",
+                            oninput: move |evt| preset_name.set(evt.value()),
+                        }
+                        button { // Salva as operações ativas do histórico com o nome informado
+                            style: "background: #10b981; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                            disabled: op_count == 0 || preset_name().trim().is_empty(),
                             onclick: move |_| {
-                                if let Some(path) = if processed_image().is_some() {
-                                    processed_image()
-                                } else {
-                                    current_image()
-                                } {
+                                let filters = session
+                                    .read()
+                                    .as_ref()
+                                    .map(|edit| edit.stack().enabled_filters())
+                                    .unwrap_or_default();
+                                let preset = Preset { name: preset_name().trim().to_string(), filters };
+                                report_error(presets::save(&preset).map(|_| ()));
+                                saved_presets.set(presets::list());
+                            },
+                            "Salvar preset"
+                        }
+                        button { // Importa um arquivo .toml recebido de outra pessoa
+                            style: "background: #3b82f6; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(file_handle) = AsyncFileDialog::new().add_filter("Preset", &["toml"]).pick_file().await {
+                                        report_error(Preset::load_file(file_handle.path()).and_then(|preset| presets::save(&preset)).map(|_| ()));
+                                        saved_presets.set(presets::list());
+                                    }
+                                });
+                            },
+                            "Importar"
+                        }
+                    }
+                    if saved_presets.read().is_empty() {
+                        div {
+                            style: "color: #9ca3af;",
+                            "Nenhum preset salvo"
+                        }
+                    }
+                    for (index, preset) in saved_presets().into_iter().enumerate() {
+                        div {
+                            key: "{preset.name}",
+                            style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem 0; border-bottom: 1px solid #e5e7eb;",
+                            span {
+                                style: "flex: 1; color: #1f2937;",
+                                "{preset.name} "
+                                span {
+                                    style: "color: #6b7280; font-size: 0.875rem;",
+                                    "({preset.filters.len()} operações)"
+                                }
+                            }
+                            button { // Aplica o preset inteiro com um clique
+                                style: "background: #374151; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                disabled: session.read().is_none(),
+                                onclick: move |_| {
+                                    if let Some(preset) = saved_presets.read().get(index).cloned() {
+                                        edit_history(&|stack| stack.extend(&preset.filters));
+                                    }
+                                },
+                                "Aplicar"
+                            }
+                            button { // Exporta o preset para compartilhar
+                                style: "background: #e5e7eb; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                onclick: move |_| {
+                                    let Some(preset) = saved_presets.read().get(index).cloned() else {
+                                        return;
+                                    };
                                     spawn(async move {
-                                        let result = image_process::add_filter(path, "rotate_90_ccw".to_string()); // chama a função de rotação de 90 graus no sentido anti-horário em image_process.rs
-                                        processed_image.set(Some(result)); // define a imagem processada como a imagem com o filtro de rotação de 90 graus no sentido anti-horário
+                                        if let Some(file_handle) = AsyncFileDialog::new()
+                                            .add_filter("Preset", &["toml"])
+                                            .set_file_name(format!("{}.toml", preset.name))
+                                            .save_file()
+                                            .await
+                                        {
+                                            report_error(preset.write_file(file_handle.path()));
+                                        }
                                     });
+                                },
+                                "Exportar"
+                            }
+                            button {
+                                style: "background: #fee2e2; color: #991b1b; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                onclick: move |_| {
+                                    if let Some(preset) = saved_presets.read().get(index).cloned() {
+                                        report_error(presets::delete(&preset));
+                                    }
+                                    saved_presets.set(presets::list());
+                                },
+                                "Excluir"
+                            }
+                        }
+                    }
+                }
+
+                div { // Processamento em lote de uma pasta inteira
+                    style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 1.5rem; margin-top: 2rem;",
+                    h3 {
+                        style: "font-size: 1.25rem; font-weight: 600; color: #1f2937; margin-bottom: 0.5rem;",
+                        "Processar pasta"
+                    }
+                    p {
+                        style: "color: #4b5563; margin-bottom: 1rem;",
+                        "Aplica as operações ativas do histórico em todas as imagens da pasta (e subpastas), gravando no formato de exportação escolhido."
+                    }
+                    div {
+                        style: "display: grid; grid-template-columns: 1fr auto; gap: 0.5rem 1rem; align-items: center; margin-bottom: 1rem;",
+                        input {
+                            style: "border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.5rem; background: #f9fafb; color: #374151;",
+                            r#type: "text",
+                            value: batch_input().map(|path| path.display().to_string()).unwrap_or_default(),
+                            readonly: true,
+                            placeholder: "Pasta de entrada..."
+                        }
+                        button {
+                            style: "background: #3b82f6; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(folder) = AsyncFileDialog::new().pick_folder().await {
+                                        batch_input.set(Some(folder.path().to_path_buf()));
+                                    }
+                                });
+                            },
+                            "Escolher entrada"
+                        }
+                        input {
+                            style: "border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.5rem; background: #f9fafb; color: #374151;",
+                            r#type: "text",
+                            value: batch_output().map(|path| path.display().to_string()).unwrap_or_default(),
+                            readonly: true,
+                            placeholder: "Pasta de saída..."
+                        }
+                        button {
+                            style: "background: #3b82f6; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(folder) = AsyncFileDialog::new().pick_folder().await {
+                                        batch_output.set(Some(folder.path().to_path_buf()));
+                                    }
+                                });
+                            },
+                            "Escolher saída"
+                        }
+                    }
+                    button {
+                        style: "background: #10b981; color: white; padding: 0.75rem 1.5rem; border-radius: 0.5rem; cursor: pointer;",
+                        disabled: batch_input().is_none() || batch_output().is_none() || batch_progress().is_some() || op_count == 0,
+                        onclick: move |_| run_batch(),
+                        "Processar"
+                    }
+                    if let Some((index, total)) = batch_progress() {
+                        div {
+                            style: "display: flex; align-items: center; gap: 1rem; margin-top: 1rem;",
+                            progress {
+                                style: "flex: 1;",
+                                value: "{index}",
+                                max: "{total}",
+                            }
+                            span { style: "color: #374151;", "{index}/{total}" }
+                        }
+                    }
+                    if let Some(report) = batch_report() {
+                        div {
+                            style: "margin-top: 1rem; color: #374151;",
+                            "{report.processed.len()} imagem(ns) processada(s), {report.failed.len()} com erro"
+                        }
+                        for failure in report.failed {
+                            div {
+                                key: "{failure.path.display()}",
+                                style: "color: #991b1b; font-size: 0.875rem; margin-top: 0.25rem;",
+                                "{failure.path.display()}: {failure.message}"
+                            }
+                        }
+                    }
+                }
+
+                div { // Calibração de lente com fotos de um tabuleiro de xadrez
+                    style: "background: white; border-radius: 0.5rem; box-shadow: 0 1px 3px rgba(0,0,0,0.1); padding: 1.5rem; margin-top: 2rem;",
+                    h3 {
+                        style: "font-size: 1.25rem; font-weight: 600; color: #1f2937; margin-bottom: 0.5rem;",
+                        "Correção de lente"
+                    }
+                    p {
+                        style: "color: #4b5563; margin-bottom: 1rem;",
+                        "Fotografe um tabuleiro de xadrez impresso com a mesma câmera e lente, de vários ângulos e cobrindo a imagem toda. A calibração calcula a distorção e salva um perfil, que pode ser aplicado em qualquer foto."
+                    }
+                    div {
+                        style: "display: grid; grid-template-columns: 1fr auto; gap: 0.5rem 1rem; align-items: center; margin-bottom: 1rem;",
+                        input {
+                            style: "border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.5rem; background: #f9fafb; color: #374151;",
+                            r#type: "text",
+                            value: lens_input().map(|path| path.display().to_string()).unwrap_or_default(),
+                            readonly: true,
+                            placeholder: "Pasta com as fotos do tabuleiro..."
+                        }
+                        button {
+                            style: "background: #3b82f6; color: white; padding: 0.5rem 1rem; border-radius: 0.5rem; cursor: pointer;",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(folder) = AsyncFileDialog::new().pick_folder().await {
+                                        lens_input.set(Some(folder.path().to_path_buf()));
+                                    }
+                                });
+                            },
+                            "Escolher pasta"
+                        }
+                    }
+                    div {
+                        style: "display: flex; flex-wrap: wrap; align-items: center; gap: 0.5rem; margin-bottom: 1rem; color: #374151;",
+                        input {
+                            style: "flex: 1; border: 1px solid #d1d5db; border-radius: 0.5rem; padding: 0.5rem;",
+                            r#type: "text",
+                            value: "{lens_name}",
+                            placeholder: "Nome do perfil...",
+                            oninput: move |evt| lens_name.set(evt.value()),
+                        }
+                        "Tabuleiro (cantos internos):"
+                        input {
+                            style: "width: 4rem; border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem;",
+                            r#type: "number",
+                            min: "2",
+                            value: "{lens_board().0}",
+                            oninput: move |evt| {
+                                if let Ok(cols) = evt.value().parse::<i32>() {
+                                    lens_board.set((cols.max(2), lens_board().1));
                                 }
                             },
-                            "Rotação de 90 graus no sentido anti-horário"
                         }
-                        button { // Botão para o filtro de reset
-                            style: "background: linear-gradient(to right, #dc2626, #991b1b); color: white; padding: 0.75rem 1rem; border-radius: 0.5rem; transition: transform 0.2s; box-shadow: 0 1px 3px rgba(0,0,0,0.1); cursor: pointer; text-align: center; align-items: center; justify-content: center;",
-                            onclick: move |_| { 
-                                if let Some(path) = current_image() {
-                                    processed_image.set(Some(path)); // define a imagem processada como a imagem original
+                        "x"
+                        input {
+                            style: "width: 4rem; border: 1px solid #d1d5db; border-radius: 0.375rem; padding: 0.25rem;",
+                            r#type: "number",
+                            min: "2",
+                            value: "{lens_board().1}",
+                            oninput: move |evt| {
+                                if let Ok(rows) = evt.value().parse::<i32>() {
+                                    lens_board.set((lens_board().0, rows.max(2)));
                                 }
                             },
-                            "Reset"
+                        }
+                    }
+                    button {
+                        style: "background: #10b981; color: white; padding: 0.75rem 1.5rem; border-radius: 0.5rem; cursor: pointer;",
+                        disabled: lens_input().is_none() || lens_name().trim().is_empty() || lens_progress().is_some(),
+                        onclick: move |_| run_calibration(),
+                        "Calibrar"
+                    }
+                    if let Some((index, total)) = lens_progress() {
+                        div {
+                            style: "display: flex; align-items: center; gap: 1rem; margin-top: 1rem;",
+                            progress {
+                                style: "flex: 1;",
+                                value: "{index}",
+                                max: "{total}",
+                            }
+                            span { style: "color: #374151;", "{index}/{total}" }
+                        }
+                    }
+                    if let Some(calibration) = lens_report() {
+                        div {
+                            style: "margin-top: 1rem; color: #374151;",
+                            "Perfil \"{calibration.lens.name}\" salvo: {calibration.lens.images} foto(s) usada(s), erro médio de {calibration.lens.error:.3} px"
+                        }
+                        for failure in calibration.rejected {
+                            div {
+                                key: "{failure.path.display()}",
+                                style: "color: #92400e; font-size: 0.875rem; margin-top: 0.25rem;",
+                                "{failure.path.display()}: {failure.message}"
+                            }
+                        }
+                    }
+                    div {
+                        style: "margin-top: 1rem;",
+                        if saved_lenses.read().is_empty() {
+                            div {
+                                style: "color: #9ca3af;",
+                                "Nenhum perfil de lente salvo"
+                            }
+                        }
+                        for (index, lens) in saved_lenses().into_iter().enumerate() {
+                            div {
+                                key: "{lens.name}",
+                                style: "display: flex; align-items: center; gap: 0.5rem; padding: 0.5rem 0; border-bottom: 1px solid #e5e7eb;",
+                                span {
+                                    style: "flex: 1; color: #1f2937;",
+                                    "{lens.name} "
+                                    span {
+                                        style: "color: #6b7280; font-size: 0.875rem;",
+                                        "(k1 = {lens.profile.k1:.4}, {lens.images} fotos)"
+                                    }
+                                }
+                                button { // Adiciona a correção da lente no fim da pilha
+                                    style: "background: #374151; color: white; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                    disabled: session.read().is_none(),
+                                    onclick: move |_| {
+                                        if let Some(lens) = saved_lenses.read().get(index).cloned() {
+                                            apply_filter(Filter::Undistort { lens: lens.profile, alpha: 0.0 });
+                                        }
+                                    },
+                                    "Aplicar"
+                                }
+                                button {
+                                    style: "background: #fee2e2; color: #991b1b; padding: 0.25rem 0.75rem; border-radius: 0.375rem; cursor: pointer;",
+                                    onclick: move |_| {
+                                        if let Some(lens) = saved_lenses.read().get(index).cloned() {
+                                            report_error(lenses::delete(&lens));
+                                        }
+                                        saved_lenses.set(lenses::list());
+                                    },
+                                    "Excluir"
+                                }
+                            }
                         }
                     }
                 }